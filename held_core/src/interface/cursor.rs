@@ -14,12 +14,18 @@ pub trait Cursor {
     fn move_to_start_of_line(&mut self);
 
     fn screen_cursor_position(&self) -> Position;
+
+    fn cursor_position(&self) -> Position;
 }
 
 pub fn screen_cursor_position() -> Position {
     get_application().screen_cursor_position()
 }
 
+pub fn cursor_position() -> Position {
+    get_application().cursor_position()
+}
+
 pub fn move_down() {
     get_application().move_down()
 }
@@ -15,6 +15,7 @@ pub mod workspace;
 pub trait ApplicationInterface: App + Buffer + Cursor + Monitor + Workspace {}
 pub static mut APPLICATION: Option<&'static mut dyn ApplicationInterface> = None;
 
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn get_application() -> &'static mut &'static mut dyn ApplicationInterface {
     unsafe {
         APPLICATION
@@ -22,3 +23,9 @@ pub(crate) fn get_application() -> &'static mut &'static mut dyn ApplicationInte
             .expect("The application has not been initialized!")
     }
 }
+
+// wasm插件中所有接口调用都经由宿主导入函数完成
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn get_application() -> &'static mut crate::wasm::guest::WasmHost {
+    crate::wasm::guest::host()
+}
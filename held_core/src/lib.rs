@@ -4,9 +4,22 @@ pub mod plugin;
 pub mod theme;
 pub mod utils;
 pub mod view;
+pub mod wasm;
 
+/// 声明插件入口，编译为动态库时导出dlopen所需符号，编译到wasm32时导出 `wit/plugin.wit` 中plugin world的函数
 #[macro_export]
 macro_rules! declare_plugin {
+    ($app:ty, $constructor:path) => {
+        #[cfg(not(target_arch = "wasm32"))]
+        $crate::__declare_dylib_plugin!($app, $constructor);
+        #[cfg(target_arch = "wasm32")]
+        $crate::__declare_wasm_plugin!($app, $constructor);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_dylib_plugin {
     ($app:ty, $constructor:path) => {
         use held_core::interface::ApplicationInterface;
         use held_core::interface::APPLICATION;
@@ -28,3 +41,20 @@ macro_rules! declare_plugin {
         }
     };
 }
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_wasm_plugin {
+    ($app:ty, $constructor:path) => {
+        struct HeldWasmPlugin;
+
+        impl $crate::wasm::guest::PluginConstructor for HeldWasmPlugin {
+            fn construct() -> Box<dyn $crate::plugin::Plugin> {
+                let constructor: fn() -> $app = $constructor;
+                Box::new(constructor())
+            }
+        }
+
+        $crate::wasm::guest::bindings::export_plugin!(HeldWasmPlugin);
+    };
+}
@@ -1,4 +1,7 @@
-use crate::view::render::ContentRenderBuffer;
+use crate::view::{
+    render::{overlay::Overlay, ContentRenderBuffer},
+    status_line::StatusSegment,
+};
 
 pub trait Plugin {
     fn name(&self) -> &'static str;
@@ -11,4 +14,14 @@ pub trait Plugin {
     fn on_render_content(&self) -> Vec<ContentRenderBuffer> {
         vec![]
     }
+
+    // 渲染buffer前会触发该回调，返回的覆盖层由Renderer按照buffer位置布局
+    fn on_render_overlays(&self) -> Vec<Overlay> {
+        vec![]
+    }
+
+    // 渲染状态栏时会触发该回调，返回的片段按照配置中的status_line布局排列
+    fn on_render_status_line(&self) -> Vec<StatusSegment> {
+        vec![]
+    }
 }
@@ -1,4 +1,30 @@
-use crossterm::style::Color;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crossterm::style::Color;
+
+/// crossterm无法编译到wasm32，插件侧使用与 `crossterm::style::Color` 定义一致的颜色
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Reset,
+    Black,
+    DarkGrey,
+    Red,
+    DarkRed,
+    Green,
+    DarkGreen,
+    Yellow,
+    DarkYellow,
+    Blue,
+    DarkBlue,
+    Magenta,
+    DarkMagenta,
+    Cyan,
+    DarkCyan,
+    White,
+    Grey,
+    Rgb { r: u8, g: u8, b: u8 },
+    AnsiValue(u8),
+}
 
 /// A convenience type used to represent a foreground/background
 /// color combination. Provides generic/convenience variants to
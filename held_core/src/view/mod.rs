@@ -1,3 +1,4 @@
 pub mod colors;
 pub mod render;
+pub mod status_line;
 pub mod style;
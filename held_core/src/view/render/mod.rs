@@ -5,6 +5,8 @@ use crate::utils::{position::Position, rectangle::Rectangle};
 use super::{colors::Colors, style::CharStyle};
 
 pub mod cell;
+pub mod overlay;
+
 pub struct ContentRenderBuffer {
     pub rectangle: Rectangle,
     pub cells: Vec<Option<Cell>>,
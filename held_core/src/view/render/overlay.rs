@@ -0,0 +1,40 @@
+use crate::{
+    utils::position::Position,
+    view::{colors::Colors, style::CharStyle},
+};
+
+/// 插件提供的高层渲染元素
+///
+/// 与 `ContentRenderBuffer` 不同，覆盖层的坐标均为buffer中的位置，
+/// 由宿主的Renderer根据滚动、行号栏以及自动换行换算为屏幕坐标。
+#[derive(Debug, Clone, PartialEq)]
+pub enum Overlay {
+    /// 锚定在buffer位置上的弹窗，优先显示在锚点下方，空间不足时显示在上方
+    Popup {
+        anchor: Position,
+        lines: Vec<String>,
+        style: CharStyle,
+        colors: Colors,
+    },
+    /// 显示在行尾之后的虚拟文本
+    VirtualText {
+        line: usize,
+        text: String,
+        style: CharStyle,
+        colors: Colors,
+    },
+    /// 显示在position处字符之前的行内提示，不会改变buffer内容
+    InlayHint {
+        position: Position,
+        text: String,
+        style: CharStyle,
+        colors: Colors,
+    },
+    /// 显示在行号栏最左侧的标记
+    GutterSign {
+        line: usize,
+        sign: char,
+        style: CharStyle,
+        colors: Colors,
+    },
+}
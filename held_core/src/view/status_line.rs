@@ -0,0 +1,13 @@
+use super::{colors::Colors, style::CharStyle};
+
+/// 插件提供的状态栏片段
+///
+/// `name` 用于在配置的 `status_line` 布局中引用该片段，
+/// 未在布局中出现的插件片段会显示在右侧区域的最前面。
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusSegment {
+    pub name: String,
+    pub content: String,
+    pub style: CharStyle,
+    pub colors: Colors,
+}
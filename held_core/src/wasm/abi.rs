@@ -0,0 +1,281 @@
+//! 按组件模型的canonical ABI从插件线性内存中读取 `wit/plugin.wit` 中导出函数的返回值
+//!
+//! 各类型在内存中的布局（偏移, 大小），记录按字段依次对齐，整体大小按最大对齐取整：
+//! - `string` / `list<T>`：`| ptr: u32 | len: u32 |`，string的len为utf-8字节数
+//! - `char-style`：u8；`color`：u8判别值之后紧跟3字节的负载，共4字节
+//! - `colors`：u8判别值之后紧跟8字节的负载，共9字节
+//! - `cell`：content (0, 4)、style (4, 1)、colors (5, 9)，共16字节
+//! - `option<cell>`：u8判别值，cell位于4，共20字节
+//! - `render-buffer`：position (0, 8)、width (8, 4)、height (12, 4)、cells (16, 8)，共24字节
+//! - `overlay`：u8判别值，负载位于4，共32字节
+//! - `status-segment`：name (0, 8)、content (8, 8)、style (16, 1)、colors (17, 9)，共28字节
+//!
+//! 所有读取都会检查边界，插件返回的数据非法时返回None。
+
+use crate::{
+    utils::{position::Position, rectangle::Rectangle},
+    view::{
+        colors::{Color, Colors},
+        render::{cell::Cell, overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+        style::CharStyle,
+    },
+};
+
+const CELL_SIZE: usize = 20;
+const RENDER_BUFFER_SIZE: usize = 24;
+const OVERLAY_SIZE: usize = 32;
+const STATUS_SEGMENT_SIZE: usize = 28;
+const STRING_SIZE: usize = 8;
+
+/// 读取返回值指针ret处的 `string`
+pub fn lift_string(memory: &[u8], ret: usize) -> Option<String> {
+    string(memory, ret)
+}
+
+/// 读取返回值指针ret处的 `list<render-buffer>`
+///
+/// 缓冲区的宽高来自插件，分配单元格之前先检查其不超过屏幕，
+/// 且与实际的单元格数一致。
+pub fn lift_render_buffers(
+    memory: &[u8],
+    ret: usize,
+    screen_width: usize,
+    screen_height: usize,
+) -> Option<Vec<ContentRenderBuffer>> {
+    list(memory, ret, RENDER_BUFFER_SIZE, |at| {
+        let position = position(memory, at)?;
+        let width = u32(memory, at + 8)?;
+        let height = u32(memory, at + 12)?;
+        if width > screen_width || height > screen_height {
+            return None;
+        }
+        let (_, len) = pointer(memory, at + 16)?;
+        if width.checked_mul(height)? != len {
+            return None;
+        }
+        let cells = list(memory, at + 16, CELL_SIZE, |at| match u8(memory, at)? {
+            0 => Some(None),
+            1 => Some(Some(Cell {
+                content: char(memory, at + 4)?,
+                style: style(memory, at + 8)?,
+                colors: colors(memory, at + 9)?,
+            })),
+            _ => None,
+        })?;
+        Some(ContentRenderBuffer {
+            rectangle: Rectangle {
+                position,
+                width,
+                height,
+            },
+            cells,
+        })
+    })
+}
+
+/// 读取返回值指针ret处的 `list<overlay>`
+pub fn lift_overlays(memory: &[u8], ret: usize) -> Option<Vec<Overlay>> {
+    list(memory, ret, OVERLAY_SIZE, |at| {
+        let payload = at + 4;
+        Some(match u8(memory, at)? {
+            0 => Overlay::Popup {
+                anchor: position(memory, payload)?,
+                lines: list(memory, payload + 8, STRING_SIZE, |at| string(memory, at))?,
+                style: style(memory, payload + 16)?,
+                colors: colors(memory, payload + 17)?,
+            },
+            1 => Overlay::VirtualText {
+                line: u32(memory, payload)?,
+                text: string(memory, payload + 4)?,
+                style: style(memory, payload + 12)?,
+                colors: colors(memory, payload + 13)?,
+            },
+            2 => Overlay::InlayHint {
+                position: position(memory, payload)?,
+                text: string(memory, payload + 8)?,
+                style: style(memory, payload + 16)?,
+                colors: colors(memory, payload + 17)?,
+            },
+            3 => Overlay::GutterSign {
+                line: u32(memory, payload)?,
+                sign: char(memory, payload + 4)?,
+                style: style(memory, payload + 8)?,
+                colors: colors(memory, payload + 9)?,
+            },
+            _ => return None,
+        })
+    })
+}
+
+/// 读取返回值指针ret处的 `list<status-segment>`
+pub fn lift_status_segments(memory: &[u8], ret: usize) -> Option<Vec<StatusSegment>> {
+    list(memory, ret, STATUS_SEGMENT_SIZE, |at| {
+        Some(StatusSegment {
+            name: string(memory, at)?,
+            content: string(memory, at + 8)?,
+            style: style(memory, at + 16)?,
+            colors: colors(memory, at + 17)?,
+        })
+    })
+}
+
+/// 将 `position` 写入插件传入的返回值指针ret处
+pub fn lower_position(memory: &mut [u8], ret: usize, position: Position) -> Option<()> {
+    let bytes = memory.get_mut(ret..ret.checked_add(8)?)?;
+    bytes[..4].copy_from_slice(&(position.line as u32).to_le_bytes());
+    bytes[4..].copy_from_slice(&(position.offset as u32).to_le_bytes());
+    Some(())
+}
+
+fn u8(memory: &[u8], at: usize) -> Option<u8> {
+    memory.get(at).copied()
+}
+
+fn u32(memory: &[u8], at: usize) -> Option<usize> {
+    let bytes = memory.get(at..at.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+}
+
+fn char(memory: &[u8], at: usize) -> Option<char> {
+    char::from_u32(u32(memory, at)? as u32)
+}
+
+fn position(memory: &[u8], at: usize) -> Option<Position> {
+    Some(Position {
+        line: u32(memory, at)?,
+        offset: u32(memory, at + 4)?,
+    })
+}
+
+fn pointer(memory: &[u8], at: usize) -> Option<(usize, usize)> {
+    Some((u32(memory, at)?, u32(memory, at + 4)?))
+}
+
+fn string(memory: &[u8], at: usize) -> Option<String> {
+    let (ptr, len) = pointer(memory, at)?;
+    let bytes = memory.get(ptr..ptr.checked_add(len)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+// 在分配之前先检查整个列表都位于内存之内
+fn list<T>(
+    memory: &[u8],
+    at: usize,
+    size: usize,
+    lift: impl Fn(usize) -> Option<T>,
+) -> Option<Vec<T>> {
+    let (ptr, len) = pointer(memory, at)?;
+    memory.get(ptr..ptr.checked_add(len.checked_mul(size)?)?)?;
+    (0..len).map(|index| lift(ptr + index * size)).collect()
+}
+
+fn style(memory: &[u8], at: usize) -> Option<CharStyle> {
+    Some(match u8(memory, at)? {
+        0 => CharStyle::Default,
+        1 => CharStyle::Bold,
+        2 => CharStyle::Reverse,
+        3 => CharStyle::Italic,
+        _ => return None,
+    })
+}
+
+fn colors(memory: &[u8], at: usize) -> Option<Colors> {
+    let payload = at + 1;
+    Some(match u8(memory, at)? {
+        0 => Colors::Default,
+        1 => Colors::Focused,
+        2 => Colors::Inverted,
+        3 => Colors::Insert,
+        4 => Colors::Warning,
+        5 => Colors::PathMode,
+        6 => Colors::SearchMode,
+        7 => Colors::SelectMode,
+        8 => Colors::CustomForeground(color(memory, payload)?),
+        9 => Colors::CustomFocusedForeground(color(memory, payload)?),
+        10 => Colors::Custom(color(memory, payload)?, color(memory, payload + 4)?),
+        _ => return None,
+    })
+}
+
+fn color(memory: &[u8], at: usize) -> Option<Color> {
+    let payload = memory.get(at + 1..at + 4)?;
+    Some(match u8(memory, at)? {
+        0 => Color::Reset,
+        1 => Color::Black,
+        2 => Color::DarkGrey,
+        3 => Color::Red,
+        4 => Color::DarkRed,
+        5 => Color::Green,
+        6 => Color::DarkGreen,
+        7 => Color::Yellow,
+        8 => Color::DarkYellow,
+        9 => Color::Blue,
+        10 => Color::DarkBlue,
+        11 => Color::Magenta,
+        12 => Color::DarkMagenta,
+        13 => Color::Cyan,
+        14 => Color::DarkCyan,
+        15 => Color::White,
+        16 => Color::Grey,
+        17 => Color::Rgb {
+            r: payload[0],
+            g: payload[1],
+            b: payload[2],
+        },
+        18 => Color::AnsiValue(payload[0]),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lift_render_buffers, lift_status_segments};
+    use crate::view::{colors::Colors, style::CharStyle};
+
+    // 依次写入小端序的u32
+    fn words(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn render_buffers_are_checked_against_screen_and_cells() {
+        // 返回值 (ptr: 8, len: 1)，render-buffer 位于8，其cells为 (ptr: 32, len: 1)
+        let buffer = |width: u32, height: u32| {
+            let mut memory = words(&[8, 1, 3, 7, width, height, 32, 1]);
+            memory.extend_from_slice(&[1, 0, 0, 0]);
+            memory.extend_from_slice(&words(&['x' as u32]));
+            memory.extend_from_slice(&[1, 4]);
+            memory.resize(52, 0);
+            memory
+        };
+
+        let buffers = lift_render_buffers(&buffer(1, 1), 0, 80, 24).unwrap();
+        assert_eq!(buffers[0].rectangle.position.line, 3);
+        let cell = buffers[0].cells[0].as_ref().unwrap();
+        assert_eq!(cell.content, 'x');
+        assert_eq!(cell.style, CharStyle::Bold);
+        assert_eq!(cell.colors, Colors::Warning);
+
+        // 宽高之积溢出
+        assert!(
+            lift_render_buffers(&buffer(u32::MAX, u32::MAX), 0, usize::MAX, usize::MAX).is_none()
+        );
+        // 大于屏幕
+        assert!(lift_render_buffers(&buffer(81, 1), 0, 80, 24).is_none());
+        // 与单元格数不一致
+        assert!(lift_render_buffers(&buffer(2, 1), 0, 80, 24).is_none());
+        // 被截断
+        assert!(lift_render_buffers(&buffer(1, 1)[..51], 0, 80, 24).is_none());
+    }
+
+    #[test]
+    fn lists_outside_memory_are_rejected() {
+        let memory = words(&[8, u32::MAX]);
+        assert!(lift_status_segments(&memory, 0).is_none());
+        assert!(lift_status_segments(&memory, 4).is_none());
+    }
+}
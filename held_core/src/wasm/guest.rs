@@ -0,0 +1,321 @@
+//! 插件侧（编译到wasm32时）的实现：由 `wit/plugin.wit` 生成绑定，
+//! 通过宿主导入函数实现 `ApplicationInterface`，并将 `Plugin` 的回调导出给宿主
+
+use std::cell::RefCell;
+
+use crate::{
+    interface::{
+        app::App, buffer::Buffer, cursor::Cursor, monitor::Monitor, workspace::Workspace,
+        ApplicationInterface,
+    },
+    plugin::Plugin,
+    utils::position::Position,
+    view::{
+        colors::{Color, Colors},
+        render::{overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+        style::CharStyle,
+    },
+};
+
+#[doc(hidden)]
+pub mod bindings {
+    wit_bindgen::generate!({
+        world: "plugin",
+        path: "wit",
+        pub_export_macro: true,
+        export_macro_name: "export_plugin",
+        default_bindings_module: "held_core::wasm::guest::bindings",
+    });
+}
+
+use bindings::held::plugin::{host as imports, types};
+
+thread_local! {
+    static PLUGIN: RefCell<Option<Box<dyn Plugin>>> = RefCell::new(None);
+}
+
+/// 宿主在插件内的代理，所有调用都转发给宿主导入函数
+pub struct WasmHost;
+
+pub fn host() -> &'static mut WasmHost {
+    // WasmHost为零大小类型，leak不会产生分配
+    Box::leak(Box::new(WasmHost))
+}
+
+impl App for WasmHost {
+    fn exit(&mut self) {
+        imports::exit()
+    }
+
+    fn to_insert_mode(&mut self) {
+        imports::to_insert_mode()
+    }
+
+    fn to_normal_mode(&mut self) {
+        imports::to_normal_mode()
+    }
+}
+
+impl Buffer for WasmHost {
+    fn insert_char(&mut self) {
+        imports::insert_char()
+    }
+
+    fn new_line(&mut self) {
+        imports::new_line()
+    }
+
+    fn insert_tab(&mut self) {
+        imports::insert_tab()
+    }
+}
+
+impl Cursor for WasmHost {
+    fn move_left(&mut self) {
+        imports::move_left()
+    }
+
+    fn move_right(&mut self) {
+        imports::move_right()
+    }
+
+    fn move_up(&mut self) {
+        imports::move_up()
+    }
+
+    fn move_down(&mut self) {
+        imports::move_down()
+    }
+
+    fn move_to_start_of_line(&mut self) {
+        imports::move_to_start_of_line()
+    }
+
+    fn screen_cursor_position(&self) -> Position {
+        imports::screen_cursor_position().into()
+    }
+
+    fn cursor_position(&self) -> Position {
+        imports::cursor_position().into()
+    }
+}
+
+impl Monitor for WasmHost {
+    fn scroll_to_cursor(&mut self) {
+        imports::scroll_to_cursor()
+    }
+
+    fn scroll_to_center(&mut self) {
+        imports::scroll_to_center()
+    }
+}
+
+impl Workspace for WasmHost {
+    fn save_file(&mut self) {
+        imports::save_file()
+    }
+
+    fn undo(&mut self) {
+        imports::undo()
+    }
+}
+
+impl ApplicationInterface for WasmHost {}
+
+/// 构造插件对象，由 `declare_plugin!` 为插件生成实现
+pub trait PluginConstructor {
+    fn construct() -> Box<dyn Plugin>;
+}
+
+// 插件对象在第一次被宿主调用时构造
+fn with_plugin<C: PluginConstructor, R>(f: impl FnOnce(&dyn Plugin) -> R) -> R {
+    PLUGIN.with(|plugin| {
+        let mut plugin = plugin.borrow_mut();
+        f(plugin.get_or_insert_with(C::construct).as_ref())
+    })
+}
+
+impl<C: PluginConstructor> bindings::Guest for C {
+    fn name() -> String {
+        with_plugin::<C, _>(|plugin| plugin.name().to_string())
+    }
+
+    fn init() {
+        with_plugin::<C, _>(|plugin| plugin.init())
+    }
+
+    fn deinit() {
+        with_plugin::<C, _>(|plugin| plugin.deinit())
+    }
+
+    fn on_render_content() -> Vec<types::RenderBuffer> {
+        let buffers = with_plugin::<C, _>(|plugin| plugin.on_render_content());
+        buffers.into_iter().map(Into::into).collect()
+    }
+
+    fn on_render_overlays() -> Vec<types::Overlay> {
+        let overlays = with_plugin::<C, _>(|plugin| plugin.on_render_overlays());
+        overlays.into_iter().map(Into::into).collect()
+    }
+
+    fn on_render_status_line() -> Vec<types::StatusSegment> {
+        let segments = with_plugin::<C, _>(|plugin| plugin.on_render_status_line());
+        segments.into_iter().map(Into::into).collect()
+    }
+}
+
+impl From<types::Position> for Position {
+    fn from(position: types::Position) -> Self {
+        Position::new(position.line as usize, position.offset as usize)
+    }
+}
+
+impl From<Position> for types::Position {
+    fn from(position: Position) -> Self {
+        types::Position {
+            line: position.line as u32,
+            offset: position.offset as u32,
+        }
+    }
+}
+
+impl From<CharStyle> for types::CharStyle {
+    fn from(style: CharStyle) -> Self {
+        match style {
+            CharStyle::Default => types::CharStyle::Default,
+            CharStyle::Bold => types::CharStyle::Bold,
+            CharStyle::Reverse => types::CharStyle::Reverse,
+            CharStyle::Italic => types::CharStyle::Italic,
+        }
+    }
+}
+
+impl From<Color> for types::Color {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Reset => types::Color::Reset,
+            Color::Black => types::Color::Black,
+            Color::DarkGrey => types::Color::DarkGrey,
+            Color::Red => types::Color::Red,
+            Color::DarkRed => types::Color::DarkRed,
+            Color::Green => types::Color::Green,
+            Color::DarkGreen => types::Color::DarkGreen,
+            Color::Yellow => types::Color::Yellow,
+            Color::DarkYellow => types::Color::DarkYellow,
+            Color::Blue => types::Color::Blue,
+            Color::DarkBlue => types::Color::DarkBlue,
+            Color::Magenta => types::Color::Magenta,
+            Color::DarkMagenta => types::Color::DarkMagenta,
+            Color::Cyan => types::Color::Cyan,
+            Color::DarkCyan => types::Color::DarkCyan,
+            Color::White => types::Color::White,
+            Color::Grey => types::Color::Grey,
+            Color::Rgb { r, g, b } => types::Color::Rgb(types::Rgb { r, g, b }),
+            Color::AnsiValue(value) => types::Color::AnsiValue(value),
+        }
+    }
+}
+
+impl From<Colors> for types::Colors {
+    fn from(colors: Colors) -> Self {
+        match colors {
+            Colors::Default => types::Colors::Default,
+            Colors::Focused => types::Colors::Focused,
+            Colors::Inverted => types::Colors::Inverted,
+            Colors::Insert => types::Colors::Insert,
+            Colors::Warning => types::Colors::Warning,
+            Colors::PathMode => types::Colors::PathMode,
+            Colors::SearchMode => types::Colors::SearchMode,
+            Colors::SelectMode => types::Colors::SelectMode,
+            Colors::CustomForeground(fg) => types::Colors::CustomForeground(fg.into()),
+            Colors::CustomFocusedForeground(fg) => {
+                types::Colors::CustomFocusedForeground(fg.into())
+            }
+            Colors::Custom(fg, bg) => types::Colors::Custom((fg.into(), bg.into())),
+        }
+    }
+}
+
+impl From<ContentRenderBuffer> for types::RenderBuffer {
+    fn from(buffer: ContentRenderBuffer) -> Self {
+        types::RenderBuffer {
+            position: buffer.rectangle.position.into(),
+            width: buffer.rectangle.width as u32,
+            height: buffer.rectangle.height as u32,
+            cells: buffer
+                .cells
+                .into_iter()
+                .map(|cell| {
+                    cell.map(|cell| types::Cell {
+                        content: cell.content,
+                        style: cell.style.into(),
+                        colors: cell.colors.into(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Overlay> for types::Overlay {
+    fn from(overlay: Overlay) -> Self {
+        match overlay {
+            Overlay::Popup {
+                anchor,
+                lines,
+                style,
+                colors,
+            } => types::Overlay::Popup(types::Popup {
+                anchor: anchor.into(),
+                lines,
+                style: style.into(),
+                colors: colors.into(),
+            }),
+            Overlay::VirtualText {
+                line,
+                text,
+                style,
+                colors,
+            } => types::Overlay::VirtualText(types::VirtualText {
+                line: line as u32,
+                text,
+                style: style.into(),
+                colors: colors.into(),
+            }),
+            Overlay::InlayHint {
+                position,
+                text,
+                style,
+                colors,
+            } => types::Overlay::InlayHint(types::InlayHint {
+                position: position.into(),
+                text,
+                style: style.into(),
+                colors: colors.into(),
+            }),
+            Overlay::GutterSign {
+                line,
+                sign,
+                style,
+                colors,
+            } => types::Overlay::GutterSign(types::GutterSign {
+                line: line as u32,
+                sign,
+                style: style.into(),
+                colors: colors.into(),
+            }),
+        }
+    }
+}
+
+impl From<StatusSegment> for types::StatusSegment {
+    fn from(segment: StatusSegment) -> Self {
+        types::StatusSegment {
+            name: segment.name,
+            content: segment.content,
+            style: segment.style.into(),
+            colors: segment.colors.into(),
+        }
+    }
+}
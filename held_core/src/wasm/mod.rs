@@ -0,0 +1,31 @@
+//! WebAssembly 插件的宿主/插件约定
+//!
+//! 接口定义在 `wit/plugin.wit` 中：插件实现 `plugin` world 的导出，宿主实现 `host` 接口。
+//! wasm 插件与动态库插件使用同一套 `Plugin` 与 `interface` 定义，
+//! `declare_plugin!` 在编译到 `wasm32` 时会由 wit-bindgen 生成的绑定导出 `Plugin` 的回调，
+//! 插件源码无需区分两种后端。
+//!
+//! 宿主使用不支持组件模型的wasmi加载插件编译出的核心模块，
+//! 按照组件模型的canonical ABI直接读取插件线性内存中的返回值（见 [`abi`]）：
+//! - 返回 `string` 或 `list` 的导出函数返回一个指针，指向 `| ptr: u32 | len: u32 |`，
+//!   宿主读取之后调用 `cabi_post_` 前缀的同名导出释放返回值
+//! - 返回 `position` 的宿主函数接收一个指针参数，宿主将结果写入该处
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod abi;
+#[cfg(target_arch = "wasm32")]
+pub mod guest;
+
+/// 宿主函数所在的导入模块名，即 `held:plugin/host` 接口
+pub const HOST_MODULE: &str = "held:plugin/host@0.1.0";
+
+pub const EXPORT_NAME: &str = "name";
+pub const EXPORT_INIT: &str = "init";
+pub const EXPORT_DEINIT: &str = "deinit";
+pub const EXPORT_ON_RENDER_CONTENT: &str = "on-render-content";
+pub const EXPORT_ON_RENDER_OVERLAYS: &str = "on-render-overlays";
+pub const EXPORT_ON_RENDER_STATUS_LINE: &str = "on-render-status-line";
+pub const EXPORT_MEMORY: &str = "memory";
+
+/// 释放导出函数返回值的导出函数名前缀
+pub const POST_RETURN_PREFIX: &str = "cabi_post_";
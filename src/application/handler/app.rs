@@ -1,27 +1,72 @@
-use crate::application::mode::{ModeData, ModeKey};
+use super::buffer;
+use crate::application::mode::prompt::{PromptAnswer, PromptData};
+use crate::application::mode::ModeKey;
 use crate::application::Application;
+use crate::buffer::Buffer;
 use crate::errors::*;
+use crate::modules::perferences::PERFERENCE_KEYS;
+use error_chain::bail;
+
+const THEME_KEY: &str = "theme";
 
 pub fn exit(app: &mut Application) -> Result<()> {
     app.switch_mode(ModeKey::Exit);
     Ok(())
 }
 
+// 有未保存修改的buffer时列出这些buffer，询问是否全部保存
+//
+// 无界面模式下没有人能回答询问，直接报错
 pub fn exit_with_check(app: &mut Application) -> Result<()> {
-    if let Some(ref buf) = app.workspace.current_buffer {
-        if buf.modified() {
-            // 输出提示(todo)
-            if let ModeData::Command(ref mut command_data) = app.mode {
-                command_data.reset();
-            }
-            app.switch_mode(ModeKey::Normal);
-        } else {
-            app.switch_mode(ModeKey::Exit);
-        }
+    let names = match app.workspace.modified_buffer_names() {
+        Some(names) => names,
+        None => return exit(app),
+    };
+    if app.headless {
+        bail!("No write since last change: {}", names);
     }
+
+    app.prompt(PromptData::confirm(
+        format!("Save changes to {}?", names),
+        &["yes", "no", "cancel"],
+        |app, answer| match answer {
+            PromptAnswer::Key('y') => save_all_and_exit(app),
+            PromptAnswer::Key('n') => exit(app),
+            _ => Ok(()),
+        },
+    ));
     Ok(())
 }
 
+/// `:wq` 保存当前buffer后退出，其他buffer有修改时仍会询问
+pub fn write_and_exit(app: &mut Application) -> Result<()> {
+    buffer::save_file(app)?;
+    exit_unless_prompted(app, exit_with_check)
+}
+
+/// `:x`、`ZZ` 与 `:wq` 相同，但仅在当前buffer有修改时保存
+pub fn save_and_exit(app: &mut Application) -> Result<()> {
+    buffer::save_if_modified(app)?;
+    exit_unless_prompted(app, exit_with_check)
+}
+
+/// `:wqa` 保存所有buffer后退出
+pub fn save_all_and_exit(app: &mut Application) -> Result<()> {
+    buffer::save_all(app)?;
+    exit_unless_prompted(app, exit)
+}
+
+// 没有路径的buffer保存时需要先输入文件名，此时不退出
+fn exit_unless_prompted(
+    app: &mut Application,
+    exit: fn(&mut Application) -> Result<()>,
+) -> Result<()> {
+    if app.mode_key == ModeKey::Prompt {
+        return Ok(());
+    }
+    exit(app)
+}
+
 pub fn to_insert_mode(app: &mut Application) -> Result<()> {
     app.switch_mode(ModeKey::Insert);
     Ok(())
@@ -33,6 +78,7 @@ pub fn to_normal_mode(app: &mut Application) -> Result<()> {
 }
 
 pub fn to_workspace_mode(app: &mut Application) -> Result<()> {
+    app.monitor.tree_panel.set_visible(true);
     app.switch_mode(ModeKey::Workspace);
     Ok(())
 }
@@ -56,3 +102,58 @@ pub fn to_replace_mode(app: &mut Application) -> Result<()> {
     app.switch_mode(ModeKey::Replace);
     Ok(())
 }
+
+// 挂起到后台，回到前台后重绘整个屏幕
+pub fn suspend(app: &mut Application) -> Result<()> {
+    app.monitor.suspend();
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    app.monitor.resume()
+}
+
+/// `:set` 列出所有设置项，`:set key` 显示设置项的值，`:set key=value` 修改设置项
+pub(super) fn set_option(app: &mut Application, args: &str) -> Result<()> {
+    let (key, value) = match args.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => {
+            let keys: Vec<&str> = match args.trim_end_matches('?') {
+                "" => PERFERENCE_KEYS.to_vec(),
+                key => {
+                    if !PERFERENCE_KEYS.contains(&key) {
+                        bail!("Unknown option: {}", key);
+                    }
+                    vec![key]
+                }
+            };
+            let perferences = app.perferences.borrow();
+            let values: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{key}={}", perferences.get(key).unwrap_or_default()))
+                .collect();
+            app.messages.info(values.join("  "));
+            return Ok(());
+        }
+    };
+
+    if key == THEME_KEY && !app.monitor.theme_names().iter().any(|name| name == value) {
+        bail!("Couldn't find \"{}\" theme", value);
+    }
+    app.perferences.borrow_mut().set(key, value)?;
+    // 主题与排版相关的设置需要重新渲染整个屏幕
+    app.monitor.redraw()
+}
+
+// 在新的buffer中列出所有历史消息
+pub fn list_messages(app: &mut Application) -> Result<()> {
+    let content: String = app
+        .messages
+        .history()
+        .map(|message| format!("{}\n", message.content))
+        .collect();
+    app.workspace
+        .add_buffer_with_select(Buffer::from_content(content));
+    app.monitor
+        .init_buffer(app.workspace.current_buffer.as_mut().unwrap())
+}
@@ -1,15 +1,27 @@
+use std::path::{Path, PathBuf};
+
 use crossterm::event::KeyCode;
 use held_core::utils::position::Position;
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::application::mode::prompt::{PromptAnswer, PromptData};
+use crate::application::mode::ModeKey;
 use crate::application::Application;
+use crate::buffer::Buffer;
 use crate::errors::*;
+use error_chain::bail;
 
 use super::cursor;
 
+// 输入文件路径的提示共享的历史
+pub(super) const PATH_HISTORY: &str = "path";
+
+// 插件也可以调用，此时可能没有打开的buffer
 pub fn insert_char(app: &mut Application) -> Result<()> {
-    if let Some(key) = app.monitor.last_key {
+    if let (Some(key), Some(buffer)) = (app.monitor.last_key, app.workspace.current_buffer.as_mut())
+    {
         if let KeyCode::Char(c) = key.code {
-            app.workspace.current_buffer.as_mut().unwrap().insert(c);
+            buffer.insert(c);
             cursor::move_right(app)?;
         }
     }
@@ -31,7 +43,12 @@ pub fn insert_char_on_replace(app: &mut Application) -> Result<()> {
 }
 
 pub fn new_line(app: &mut Application) -> Result<()> {
+    let break_undo =
+        app.mode_key == ModeKey::Insert && app.perferences.borrow().undo_break_on_newline();
     if let Some(ref mut buffer) = app.workspace.current_buffer {
+        if break_undo {
+            buffer.break_operation_group();
+        }
         buffer.insert('\n');
     }
     Ok(())
@@ -67,9 +84,189 @@ pub fn redo(app: &mut Application) -> Result<()> {
 }
 
 pub fn save_file(app: &mut Application) -> Result<()> {
+    let buffer = match app.workspace.current_buffer {
+        Some(ref mut buffer) => buffer,
+        None => return Ok(()),
+    };
+    let path = match buffer.path {
+        Some(ref path) => path.to_string_lossy().to_string(),
+        None => {
+            // 没有路径的buffer先询问文件名
+            app.prompt(PromptData::input(
+                "Save as:".to_string(),
+                PATH_HISTORY,
+                |app, answer| match answer {
+                    PromptAnswer::Text(path) if !path.trim().is_empty() => {
+                        save_as(app, path.trim())
+                    }
+                    _ => Ok(()),
+                },
+            ));
+            return Ok(());
+        }
+    };
+    buffer.save()?;
+    app.messages.info(format!("\"{path}\" written"));
+    Ok(())
+}
+
+/// `:wa` 保存所有修改过的buffer
+///
+/// 当前buffer没有路径时询问文件名，其他没有路径的buffer无法保存。
+pub fn save_all(app: &mut Application) -> Result<()> {
+    let current = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .and_then(|buffer| buffer.id);
+    let mut written = 0;
+    let mut unnamed = Vec::new();
+    for id in app.workspace.modified_buffer_ids() {
+        let buffer = match app.workspace.get_buffer_mut(id) {
+            Some(buffer) => buffer,
+            None => continue,
+        };
+        if buffer.path.is_none() {
+            unnamed.push(id);
+            continue;
+        }
+        buffer.save().chain_err(|| {
+            format!(
+                "Couldn't write \"{}\"",
+                buffer.file_name().unwrap_or_default()
+            )
+        })?;
+        written += 1;
+    }
+    if written > 0 {
+        app.messages.info(format!("{written} file(s) written"));
+    }
+
+    if let Some(id) = unnamed.iter().find(|id| Some(**id) != current) {
+        bail!("No file name for buffer {}", id);
+    }
+    if !unnamed.is_empty() {
+        save_file(app)?;
+    }
+    Ok(())
+}
+
+/// `:x` 仅在当前buffer有修改时保存
+pub fn save_if_modified(app: &mut Application) -> Result<()> {
+    match app.workspace.current_buffer {
+        Some(ref buffer) if buffer.modified() => save_file(app),
+        _ => Ok(()),
+    }
+}
+
+// 写入到新的路径，文件已经存在时确认是否覆盖
+pub(super) fn save_as(app: &mut Application, path: &str) -> Result<()> {
+    let path = app.workspace.path.join(path);
+    if !path.exists() {
+        return write_to(app, path);
+    }
+
+    app.prompt(PromptData::confirm(
+        format!("\"{}\" exists, overwrite?", path.to_string_lossy()),
+        &["yes", "no"],
+        move |app, answer| match answer {
+            PromptAnswer::Key('y') => write_to(app, path),
+            _ => Ok(()),
+        },
+    ));
+    Ok(())
+}
+
+fn write_to(app: &mut Application, path: PathBuf) -> Result<()> {
     if let Some(ref mut buffer) = app.workspace.current_buffer {
-        buffer.save()?;
+        buffer.path = Some(path);
     }
+    save_file(app)
+}
+
+/// `:e path` 打开相对于工作区的文件
+pub(super) fn open(app: &mut Application, path: &str) -> Result<()> {
+    app.workspace
+        .open_buffer(Path::new(path), &mut app.monitor)
+        .chain_err(|| format!("Couldn't open \"{path}\""))?;
+    Ok(())
+}
+
+/// `:b name` 按id或名字切换buffer，名字可以是唯一匹配的一部分
+pub(super) fn switch_to(app: &mut Application, name: &str) -> Result<()> {
+    if name.is_empty() {
+        return list_buffers(app);
+    }
+
+    let ids = app.workspace.buffer_ids();
+    let id = match name.parse::<usize>() {
+        Ok(id) if ids.contains(&id) => id,
+        _ => {
+            let named: Vec<(usize, String)> = ids
+                .into_iter()
+                .filter_map(|id| {
+                    let buffer = app.workspace.get_buffer(id)?;
+                    Some((id, app.workspace.buffer_name(buffer)?))
+                })
+                .collect();
+            let matched: Vec<usize> = match named.iter().find(|(_, buffer)| buffer == name) {
+                Some((id, _)) => vec![*id],
+                None => named
+                    .iter()
+                    .filter(|(_, buffer)| buffer.contains(name))
+                    .map(|(id, _)| *id)
+                    .collect(),
+            };
+            if matched.is_empty() {
+                bail!("No matching buffer for {}", name);
+            }
+            if matched.len() > 1 {
+                bail!("More than one match for {}", name);
+            }
+            matched[0]
+        }
+    };
+    app.workspace.select_buffer(id);
+    Ok(())
+}
+
+// 在列表中选择要切换到的buffer
+pub fn list_buffers(app: &mut Application) -> Result<()> {
+    let ids = app.workspace.buffer_ids();
+    let current = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .and_then(|buffer| buffer.id);
+    let items = ids
+        .iter()
+        .filter_map(|id| app.workspace.get_buffer(*id))
+        .map(|buffer| {
+            let id = buffer.id.unwrap_or_default();
+            format!(
+                "{id:>3} {} {}{}",
+                if Some(id) == current { '%' } else { ' ' },
+                buffer
+                    .path
+                    .as_ref()
+                    .map_or("[No Name]".to_string(), |path| path
+                        .to_string_lossy()
+                        .to_string()),
+                if buffer.modified() { " [+]" } else { "" },
+            )
+        })
+        .collect();
+
+    app.prompt(PromptData::choice(
+        "Buffers:".to_string(),
+        items,
+        move |app, answer| {
+            if let PromptAnswer::Choice(index) = answer {
+                app.workspace.select_buffer(ids[index]);
+            }
+            Ok(())
+        },
+    ));
     Ok(())
 }
 
@@ -79,3 +276,70 @@ pub fn undo(app: &mut Application) -> Result<()> {
     }
     Ok(())
 }
+
+// 粘贴的文本作为一次插入写入buffer，不经过按键绑定
+pub fn paste(app: &mut Application) -> Result<()> {
+    if let (Some(text), Some(buffer)) = (
+        app.monitor.last_paste.take(),
+        app.workspace.current_buffer.as_mut(),
+    ) {
+        insert_text(buffer, &normalize_line_endings(&text));
+    }
+    Ok(())
+}
+
+// Normal模式下粘贴的文本存入寄存器，由 `put` 写入buffer
+pub fn paste_to_register(app: &mut Application) -> Result<()> {
+    if let Some(text) = app.monitor.last_paste.take() {
+        app.register = Some(normalize_line_endings(&text));
+    }
+    Ok(())
+}
+
+// 在光标之后写入寄存器中的文本，光标停在写入的最后一个字符上
+pub fn put(app: &mut Application) -> Result<()> {
+    if let (Some(text), Some(buffer)) =
+        (app.register.clone(), app.workspace.current_buffer.as_mut())
+    {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let line_empty = buffer
+            .data()
+            .lines()
+            .nth(buffer.cursor.line)
+            .is_none_or(str::is_empty);
+        if !line_empty {
+            buffer.cursor.move_to(Position {
+                line: buffer.cursor.line,
+                offset: buffer.cursor.offset + 1,
+            });
+        }
+        insert_text(buffer, &text);
+        buffer.cursor.move_left();
+    }
+    Ok(())
+}
+
+/// 插入文本并将光标移动到文本之后
+fn insert_text(buffer: &mut Buffer, text: &str) {
+    let start = *buffer.cursor;
+    buffer.insert(text);
+
+    let end = match text.rsplit_once('\n') {
+        Some((head, last_line)) => Position {
+            line: start.line + head.matches('\n').count() + 1,
+            offset: last_line.graphemes(true).count(),
+        },
+        None => Position {
+            line: start.line,
+            offset: start.offset + text.graphemes(true).count(),
+        },
+    };
+    buffer.cursor.move_to(end);
+}
+
+// 终端粘贴的换行可能是 `\r\n` 或 `\r`
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
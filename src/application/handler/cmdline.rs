@@ -0,0 +1,115 @@
+use crossterm::event::KeyCode;
+
+use crate::application::mode::ModeData;
+use crate::application::Application;
+use crate::errors::*;
+use crate::modules::cmdline::LineEditor;
+
+/// 编辑命令行、搜索、查找文件或过滤目录树的输入，执行搜索后不再接受编辑
+///
+/// 修改文本时停止浏览历史，任何编辑都会关闭补全菜单。
+pub(super) fn edit<F>(app: &mut Application, modify: bool, edit: F)
+where
+    F: FnOnce(&mut LineEditor),
+{
+    let (input, history) = match app.mode {
+        ModeData::Command(ref mut command_data) => {
+            command_data.completion = None;
+            (&mut command_data.input, Some(&mut command_data.history))
+        }
+        ModeData::Search(ref mut search_data) if !search_data.is_exec_search => {
+            (&mut search_data.input, Some(&mut search_data.history))
+        }
+        ModeData::Finder(ref mut finder_data) => (&mut finder_data.input, None),
+        ModeData::TreeFilter(ref mut input) => (input, None),
+        _ => return,
+    };
+    edit(input);
+    if let (true, Some(history)) = (modify, history) {
+        history.reset();
+    }
+    // 目录树随输入过滤
+    if let ModeData::TreeFilter(ref input) = app.mode {
+        app.monitor.tree_panel.tree.set_query(input.text());
+    }
+}
+
+pub fn insert_char(app: &mut Application) -> Result<()> {
+    if let Some(KeyCode::Char(c)) = app.monitor.last_key.map(|key| key.code) {
+        edit(app, true, |input| input.insert_char(c));
+    }
+    Ok(())
+}
+
+pub fn backspace(app: &mut Application) -> Result<()> {
+    edit(app, true, |input| {
+        input.backspace();
+    });
+    Ok(())
+}
+
+pub fn move_left(app: &mut Application) -> Result<()> {
+    edit(app, false, LineEditor::move_left);
+    Ok(())
+}
+
+pub fn move_right(app: &mut Application) -> Result<()> {
+    edit(app, false, LineEditor::move_right);
+    Ok(())
+}
+
+pub fn move_to_start(app: &mut Application) -> Result<()> {
+    edit(app, false, LineEditor::move_to_start);
+    Ok(())
+}
+
+pub fn move_to_end(app: &mut Application) -> Result<()> {
+    edit(app, false, LineEditor::move_to_end);
+    Ok(())
+}
+
+pub fn delete(app: &mut Application) -> Result<()> {
+    edit(app, true, LineEditor::delete);
+    Ok(())
+}
+
+pub fn delete_word(app: &mut Application) -> Result<()> {
+    edit(app, true, LineEditor::delete_word);
+    Ok(())
+}
+
+pub fn delete_to_start(app: &mut Application) -> Result<()> {
+    edit(app, true, LineEditor::delete_to_start);
+    Ok(())
+}
+
+// 以已输入的文本为前缀查看上一条历史
+pub fn history_previous(app: &mut Application) -> Result<()> {
+    browse_history(app, false);
+    Ok(())
+}
+
+pub fn history_next(app: &mut Application) -> Result<()> {
+    browse_history(app, true);
+    Ok(())
+}
+
+fn browse_history(app: &mut Application, forward: bool) {
+    let (input, history) = match app.mode {
+        ModeData::Command(ref mut command_data) => {
+            command_data.completion = None;
+            (&mut command_data.input, &mut command_data.history)
+        }
+        ModeData::Search(ref mut search_data) if !search_data.is_exec_search => {
+            (&mut search_data.input, &mut search_data.history)
+        }
+        _ => return,
+    };
+    let entry = match forward {
+        true => history.next(),
+        false => history.previous(input.text()),
+    };
+    if let Some(entry) = entry {
+        input.set(entry);
+    }
+}
@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 
-use crossterm::event::KeyCode;
+use std::path::Path;
+
 use smallvec::SmallVec;
 
-use crate::application::handler::{app, buffer};
+use crate::application::handler::{app, buffer, cmdline, keymap, quickfix, search, shell};
+use crate::application::mode::command::Completion;
 use crate::application::mode::{ModeData, ModeKey};
 use crate::application::Application;
 use crate::errors::*;
+use crate::modules::perferences::PERFERENCE_KEYS;
 
 use lazy_static::lazy_static;
 
@@ -34,8 +37,70 @@ lazy_static! {
         cmd_map.insert(
             "wq".to_string(),
             SmallVec::from_vec(vec![
-                buffer::save_file as fn(&mut Application) -> Result<()>,
-                app::exit as fn(&mut Application) -> Result<()>,
+                app::write_and_exit as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "x".to_string(),
+            SmallVec::from_vec(vec![
+                app::save_and_exit as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "qa".to_string(),
+            SmallVec::from_vec(vec![
+                app::exit_with_check as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "qa!".to_string(),
+            SmallVec::from_vec(vec![app::exit as fn(&mut Application) -> Result<()>]),
+        );
+        cmd_map.insert(
+            "wa".to_string(),
+            SmallVec::from_vec(vec![buffer::save_all as fn(&mut Application) -> Result<()>]),
+        );
+        cmd_map.insert(
+            "wqa".to_string(),
+            SmallVec::from_vec(vec![
+                app::save_all_and_exit as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "messages".to_string(),
+            SmallVec::from_vec(vec![
+                app::list_messages as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "ls".to_string(),
+            SmallVec::from_vec(vec![
+                buffer::list_buffers as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        cmd_map.insert(
+            "buffers".to_string(),
+            SmallVec::from_vec(vec![
+                buffer::list_buffers as fn(&mut Application) -> Result<()>,
+            ]),
+        );
+        for (name, handler) in [
+            (
+                "copen",
+                quickfix::open as fn(&mut Application) -> Result<()>,
+            ),
+            ("cn", quickfix::next),
+            ("cnext", quickfix::next),
+            ("cp", quickfix::previous),
+            ("cprev", quickfix::previous),
+            ("cprevious", quickfix::previous),
+        ] {
+            cmd_map.insert(name.to_string(), SmallVec::from_vec(vec![handler]));
+        }
+        cmd_map.insert(
+            "maps".to_string(),
+            SmallVec::from_vec(vec![
+                keymap::list_maps as fn(&mut Application) -> Result<()>,
             ]),
         );
         cmd_map
@@ -44,59 +109,236 @@ lazy_static! {
 
 pub fn commit_and_execute(app: &mut Application) -> Result<()> {
     let cmd = match app.mode {
-        ModeData::Command(ref mut command_data) => command_data.input.clone(),
-        _ => String::new(),
-    };
-    // 匹配命令执行
-    match COMMAND.get(&cmd).cloned() {
-        Some(fucs) => {
-            for fuc in fucs {
-                fuc(app)?;
-            }
-            // 执行完函数清空数据
-            if let ModeData::Command(ref mut command_data) = app.mode {
-                command_data.reset();
-            }
-            if app.mode_key != ModeKey::Exit {
-                app::to_normal_mode(app)?;
-            }
-        }
-        None => {
-            if let ModeData::Command(ref mut command_data) = app.mode {
-                command_data.reset();
+        ModeData::Command(ref mut command_data) => {
+            let cmd = command_data.input.text().to_string();
+            let saved = command_data.history.push(&cmd);
+            command_data.reset();
+            // 历史无法写入时仍然执行命令
+            if let Err(e) = saved {
+                app.messages.error(&e);
             }
-            app::to_normal_mode(app)?;
+            cmd
         }
+        _ => String::new(),
+    };
+    // 匹配命令执行，命令可能切换到其他模式（如退出或提示），仍处于命令模式时回到Normal模式
+    let result = execute(app, &cmd);
+    if app.mode_key == ModeKey::Command {
+        app::to_normal_mode(app)?;
     }
-    // 匹配完reset
-    if let ModeData::Command(ref mut command_data) = app.mode {
-        command_data.reset();
+    if let Ok(false) = result {
+        app.messages
+            .warning(format!("Not an editor command: {}", cmd.trim()));
     }
-    Ok(())
+    result.map(|_| ())
 }
 
-pub fn insert_command(app: &mut Application) -> Result<()> {
-    if let Some(key) = app.monitor.last_key {
-        if let KeyCode::Char(c) = key.code {
-            if let ModeData::Command(ref mut command_data) = app.mode {
-                command_data.input.insert(command_data.input.len(), c);
-            }
+/// 执行一条命令，命令不存在时返回false
+///
+/// 交互模式与无界面的脚本模式共用该入口。
+pub(crate) fn execute(app: &mut Application, cmd: &str) -> Result<bool> {
+    if let Some(fucs) = COMMAND.get(cmd).cloned() {
+        for fuc in fucs {
+            fuc(app)?;
         }
+        return Ok(true);
     }
-    Ok(())
+
+    // shell命令
+    if let Some(command) = cmd.strip_prefix('!') {
+        shell::run(app, command.trim())?;
+        return Ok(true);
+    }
+    if let Some((range, command)) = shell::split_filter(cmd) {
+        shell::filter(app, range, command)?;
+        return Ok(true);
+    }
+
+    // 带参数的命令
+    let (name, args) = cmd.split_once(' ').unwrap_or((cmd, ""));
+    match name {
+        "map" => keymap::map(app, None, args)?,
+        "nmap" => keymap::map(app, Some(ModeKey::Normal), args)?,
+        "imap" => keymap::map(app, Some(ModeKey::Insert), args)?,
+        "unmap" => keymap::unmap(app, None, args)?,
+        "nunmap" => keymap::unmap(app, Some(ModeKey::Normal), args)?,
+        "iunmap" => keymap::unmap(app, Some(ModeKey::Insert), args)?,
+        "r" | "read" if args.starts_with('!') => shell::read(app, args[1..].trim())?,
+        "e" | "edit" if !args.trim().is_empty() => buffer::open(app, args.trim())?,
+        "w" | "write" if !args.trim().is_empty() => buffer::save_as(app, args.trim())?,
+        "b" | "buffer" => buffer::switch_to(app, args.trim())?,
+        "set" => app::set_option(app, args.trim())?,
+        "grep" => search::grep(app, args.trim())?,
+        "cf" | "cfile" => quickfix::load_file(app, args.trim())?,
+        "make" => quickfix::make(app, args.trim())?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+pub fn insert_command(app: &mut Application) -> Result<()> {
+    cmdline::insert_char(app)
 }
 
 pub fn backspace(app: &mut Application) -> Result<()> {
-    if let ModeData::Command(ref mut command_data) = app.mode {
+    if let ModeData::Command(ref command_data) = app.mode {
         if command_data.input.is_empty() {
-            return app::to_normal_mode(app);
-        } else {
-            command_data.input.remove(command_data.input.len() - 1);
+            return to_normal_mode(app);
         }
     }
+    cmdline::backspace(app)
+}
+
+// 补全光标前的命令名或参数，再次按下时选择下一个候选项
+pub fn complete(app: &mut Application) -> Result<()> {
+    cycle_completion(app, true);
+    Ok(())
+}
+
+pub fn complete_previous(app: &mut Application) -> Result<()> {
+    cycle_completion(app, false);
     Ok(())
 }
 
+fn cycle_completion(app: &mut Application, forward: bool) {
+    let before = match app.mode {
+        ModeData::Command(ref command_data) if command_data.completion.is_none() => {
+            command_data.input.before_cursor().to_string()
+        }
+        ModeData::Command(_) => String::new(),
+        _ => return,
+    };
+
+    // 第一次按下时生成候选项
+    let completion = match app.mode {
+        ModeData::Command(ref command_data) if command_data.completion.is_none() => {
+            let (start, candidates) = completion_candidates(app, &before);
+            Some(Completion {
+                start,
+                candidates,
+                selected: None,
+            })
+        }
+        _ => None,
+    };
+
+    let command_data = match app.mode {
+        ModeData::Command(ref mut command_data) => command_data,
+        _ => return,
+    };
+    if let Some(completion) = completion {
+        if completion.candidates.is_empty() {
+            return;
+        }
+        command_data.completion = Some(completion);
+    }
+    let completion = match command_data.completion.as_mut() {
+        Some(completion) => completion,
+        None => return,
+    };
+
+    let count = completion.candidates.len();
+    let selected = match (completion.selected, forward) {
+        (None, true) => 0,
+        (None, false) => count - 1,
+        (Some(index), true) => (index + 1) % count,
+        (Some(index), false) => (index + count - 1) % count,
+    };
+    completion.selected = Some(selected);
+    command_data
+        .input
+        .replace_before_cursor(completion.start, &completion.candidates[selected]);
+    command_data.history.reset();
+    // 唯一的候选项直接填入，不显示菜单
+    if count == 1 {
+        command_data.completion = None;
+    }
+}
+
+// 需要参数的命令，用于补全命令名
+const ARG_COMMANDS: &[&str] = &[
+    "buffer", "cfile", "edit", "grep", "imap", "iunmap", "make", "map", "nmap", "nunmap", "read",
+    "set", "unmap", "write",
+];
+
+// 光标前的文本中需要补全的部分的起始位置，以及候选项
+fn completion_candidates(app: &Application, before: &str) -> (usize, Vec<String>) {
+    let (name, args) = match before.split_once(' ') {
+        Some(split) => split,
+        None => {
+            let mut names: Vec<String> = COMMAND
+                .keys()
+                .map(|name| name.as_str())
+                .chain(ARG_COMMANDS.iter().copied())
+                .filter(|name| name.starts_with(before))
+                .map(|name| name.to_string())
+                .collect();
+            names.sort();
+            names.dedup();
+            return (0, names);
+        }
+    };
+    let start = before.len() - args.len();
+
+    let candidates = match name {
+        "e" | "edit" | "w" | "write" | "cf" | "cfile" => complete_path(&app.workspace.path, args),
+        "b" | "buffer" => app
+            .workspace
+            .buffer_ids()
+            .into_iter()
+            .filter_map(|id| app.workspace.get_buffer(id))
+            .filter_map(|buffer| app.workspace.buffer_name(buffer))
+            .filter(|name| name.starts_with(args))
+            .collect(),
+        "set" => match args.split_once('=') {
+            Some((key, value)) if key == "theme" => {
+                let start = start + key.len() + 1;
+                let themes = app
+                    .monitor
+                    .theme_names()
+                    .into_iter()
+                    .filter(|theme| theme.starts_with(value))
+                    .collect();
+                return (start, themes);
+            }
+            Some(_) => Vec::new(),
+            None => PERFERENCE_KEYS
+                .iter()
+                .filter(|key| key.starts_with(args))
+                .map(|key| key.to_string())
+                .collect(),
+        },
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
+
+// 补全相对于工作区的路径，目录以 `/` 结尾，以 `.` 开头时才列出隐藏文件
+fn complete_path(base: &Path, arg: &str) -> Vec<String> {
+    let (dir, prefix) = match arg.rfind('/') {
+        Some(index) => arg.split_at(index + 1),
+        None => ("", arg),
+    };
+    let entries = match base.join(dir).read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let is_dir = entry.path().is_dir();
+            Some(format!("{dir}{name}{}", if is_dir { "/" } else { "" }))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
 pub fn to_normal_mode(app: &mut Application) -> Result<()> {
     if let ModeData::Command(ref mut command_data) = app.mode {
         command_data.reset();
@@ -0,0 +1,53 @@
+use crate::application::mode::{ModeData, ModeKey};
+use crate::application::Application;
+use crate::errors::*;
+
+// 列出工作区中的文件，开始模糊查找
+pub fn open(app: &mut Application) -> Result<()> {
+    let ignore_patterns = app.perferences.borrow().ignore_patterns();
+    app.switch_mode(ModeKey::Finder);
+    if let ModeData::Finder(ref mut finder_data) = app.mode {
+        finder_data.load(&app.workspace.path, &ignore_patterns);
+    }
+    Ok(())
+}
+
+pub fn previous(app: &mut Application) -> Result<()> {
+    if let ModeData::Finder(ref mut finder_data) = app.mode {
+        finder_data.move_selection(false);
+    }
+    Ok(())
+}
+
+pub fn next(app: &mut Application) -> Result<()> {
+    if let ModeData::Finder(ref mut finder_data) = app.mode {
+        finder_data.move_selection(true);
+    }
+    Ok(())
+}
+
+// 打开所选的文件
+pub fn confirm(app: &mut Application) -> Result<()> {
+    let path = match app.mode {
+        ModeData::Finder(ref mut finder_data) => {
+            finder_data.update_matches();
+            finder_data.selected_path()
+        }
+        _ => None,
+    };
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    app.switch_mode(ModeKey::Normal);
+    app.workspace
+        .open_buffer(&path, &mut app.monitor)
+        .chain_err(|| format!("Couldn't open \"{}\"", path.to_string_lossy()))?;
+    Ok(())
+}
+
+pub fn cancel(app: &mut Application) -> Result<()> {
+    app.switch_mode(ModeKey::Normal);
+    Ok(())
+}
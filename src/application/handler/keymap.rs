@@ -0,0 +1,103 @@
+use crate::application::mode::ModeKey;
+use crate::application::Application;
+use crate::buffer::Buffer;
+use crate::errors::*;
+use crate::modules::input::keymap::{display_keys, parse_notation, KeyAction};
+
+use super::handle_map;
+
+/// `:map [mode] {keys} {commands}`，commands为处理函数名，或以 `@` 开头的宏寄存器
+///
+/// mode为None时第一个参数可以是配置文件中的任一模式名，省略时为normal模式。
+/// 只有其后还有按键与命令时才视为模式名，因此仍可以映射名为 `insert` 等的按键。
+pub(super) fn map(app: &mut Application, mode: Option<ModeKey>, args: &str) -> Result<()> {
+    let args: Vec<&str> = args.split_whitespace().collect();
+    let (mode, args) = split_mode(mode, &args, 3);
+    let keys = match args.first() {
+        Some(notation) => parse_notation(notation, app.leader())?,
+        // 不带参数时列出所有绑定
+        None => return list_maps(app),
+    };
+    let action = KeyAction::parse(&args[1..], &handle_map())?;
+    app.map_keys(mode, &keys, action)
+}
+
+/// `:unmap [mode] {keys}`，mode与 `:map` 相同
+pub(super) fn unmap(app: &mut Application, mode: Option<ModeKey>, args: &str) -> Result<()> {
+    let args: Vec<&str> = args.split_whitespace().collect();
+    let (mode, args) = split_mode(mode, &args, 2);
+    let keys = parse_notation(&args.join(" "), app.leader())?;
+    app.unmap_keys(mode, &keys)
+}
+
+// 参数不少于min个且第一个为模式名时将其取出
+fn split_mode<'a, 'b>(
+    mode: Option<ModeKey>,
+    args: &'a [&'b str],
+    min: usize,
+) -> (ModeKey, &'a [&'b str]) {
+    if let Some(mode) = mode {
+        return (mode, args);
+    }
+    match args.first().and_then(|name| ModeKey::from_name(name)) {
+        Some(mode) if args.len() >= min => (mode, &args[1..]),
+        _ => (ModeKey::Normal, args),
+    }
+}
+
+/// 在新的buffer中列出各模式当前生效的绑定
+pub fn list_maps(app: &mut Application) -> Result<()> {
+    let mut modes: Vec<_> = app.keymaps().iter().collect();
+    modes.sort_by_key(|(mode, _)| mode.as_str());
+
+    let mut content = String::new();
+    for (mode, keymap) in modes {
+        let bindings: Vec<_> = keymap
+            .bindings()
+            .into_iter()
+            .map(|(keys, action)| (display_keys(&keys), action.describe()))
+            .collect();
+        if bindings.is_empty() {
+            continue;
+        }
+
+        let width = bindings
+            .iter()
+            .map(|(keys, _)| keys.len())
+            .max()
+            .unwrap_or(0);
+        content.push_str(&format!("{}:\n", mode));
+        for (keys, action) in bindings {
+            content.push_str(&format!("  {:width$}  {}\n", keys, action));
+        }
+    }
+
+    app.workspace
+        .add_buffer_with_select(Buffer::from_content(content));
+    app.monitor
+        .init_buffer(app.workspace.current_buffer.as_mut().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_mode;
+    use crate::application::mode::ModeKey;
+
+    #[test]
+    fn leading_mode_name_is_only_taken_before_keys_and_commands() {
+        let args = ["tree_filter", "<ctrl-c>", "tree::cancel"];
+        assert_eq!(
+            split_mode(None, &args, 3),
+            (ModeKey::TreeFilter, &args[1..])
+        );
+        // 名为insert的按键
+        let args = ["insert", "normal::move_up"];
+        assert_eq!(split_mode(None, &args, 3), (ModeKey::Normal, &args[..]));
+        let args = ["workspace", "x"];
+        assert_eq!(split_mode(None, &args, 2), (ModeKey::Workspace, &args[1..]));
+        assert_eq!(
+            split_mode(Some(ModeKey::Insert), &args, 2),
+            (ModeKey::Insert, &args[..])
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use held_core::utils::position::Position;
+
+use crate::application::mode::{ModeData, ModeKey};
+use crate::application::Application;
+use crate::errors::*;
+use crate::modules::location::Location;
+
+/// 在列表模式中显示快速修复列表，选中最近跳转到的项
+pub(super) fn show(app: &mut Application) {
+    app.switch_mode(ModeKey::List);
+    if let ModeData::List(ref mut list_data) = app.mode {
+        list_data.load(
+            app.quickfix.title().to_string(),
+            &app.workspace.path,
+            app.quickfix.items().to_vec(),
+            app.quickfix.current().unwrap_or(0),
+        );
+    }
+}
+
+/// 打开location所在的文件并将光标移到该位置
+pub(super) fn jump_to(app: &mut Application, location: &Location) -> Result<()> {
+    app.workspace
+        .open_buffer(&location.path, &mut app.monitor)
+        .chain_err(|| format!("Couldn't open \"{}\"", location.path.to_string_lossy()))?;
+    if let Some(ref mut buffer) = app.workspace.current_buffer {
+        buffer.cursor.move_to(Position {
+            line: location.line,
+            offset: location.column,
+        });
+        app.monitor.scroll_to_center(buffer)?;
+    }
+    Ok(())
+}
+
+pub fn previous(app: &mut Application) -> Result<()> {
+    if let ModeData::List(ref mut list_data) = app.mode {
+        list_data.move_selection(false);
+    }
+    Ok(())
+}
+
+pub fn next(app: &mut Application) -> Result<()> {
+    if let ModeData::List(ref mut list_data) = app.mode {
+        list_data.move_selection(true);
+    }
+    Ok(())
+}
+
+pub fn first(app: &mut Application) -> Result<()> {
+    if let ModeData::List(ref mut list_data) = app.mode {
+        list_data.select_first();
+    }
+    Ok(())
+}
+
+pub fn last(app: &mut Application) -> Result<()> {
+    if let ModeData::List(ref mut list_data) = app.mode {
+        list_data.select_last();
+    }
+    Ok(())
+}
+
+// 跳转到所选的位置，之后的 `:cnext` 从该项继续
+pub fn confirm(app: &mut Application) -> Result<()> {
+    let selected = match app.mode {
+        ModeData::List(ref list_data) => list_data.selected(),
+        _ => return Ok(()),
+    };
+    let location = match app.quickfix.select(selected) {
+        Some(location) => location.clone(),
+        None => return Ok(()),
+    };
+
+    app.switch_mode(ModeKey::Normal);
+    jump_to(app, &location)
+}
+
+pub fn close(app: &mut Application) -> Result<()> {
+    app.switch_mode(ModeKey::Normal);
+    Ok(())
+}
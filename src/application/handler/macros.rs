@@ -0,0 +1,19 @@
+use crate::application::Application;
+use crate::errors::*;
+use crate::modules::macros::MacroPending;
+
+/// 录制中再次按下时结束录制，否则等待输入寄存器名
+pub fn record(app: &mut Application) -> Result<()> {
+    if app.macros.recording().is_some() {
+        // 结束录制的按键已经被记录，需要丢弃
+        app.macros.stop_recording(true)?;
+    } else {
+        app.macros.pending = Some(MacroPending::Record);
+    }
+    Ok(())
+}
+
+pub fn play(app: &mut Application) -> Result<()> {
+    app.macros.pending = Some(MacroPending::Play);
+    Ok(())
+}
@@ -2,15 +2,25 @@ use std::collections::HashMap;
 
 use super::Application;
 use crate::errors::*;
-mod app;
-mod buffer;
-mod command;
-mod cursor;
+pub(super) mod app;
+pub(super) mod buffer;
+mod cmdline;
+pub(super) mod command;
+pub(super) mod cursor;
 mod delete;
+mod finder;
 mod insert;
-mod monitor;
+mod keymap;
+mod list;
+mod macros;
+pub(super) mod monitor;
+mod mouse;
 mod normal;
+mod prompt;
+mod quickfix;
 mod search;
+mod select;
+mod shell;
 mod workspace;
 
 pub fn handle_map() -> HashMap<&'static str, fn(&mut Application) -> Result<()>> {
@@ -0,0 +1,86 @@
+use held_core::utils::position::Position;
+
+use crate::application::mode::ModeKey;
+use crate::application::Application;
+use crate::errors::*;
+
+use super::{select, workspace};
+
+// 滚轮每次滚动的行数
+const SCROLL_LINES: usize = 3;
+
+// 点击文本区域移动光标，选择模式下点击会取消选择
+pub fn click(app: &mut Application) -> Result<()> {
+    if tree_panel_row(app)?.is_some() {
+        return workspace::click(app);
+    }
+    if app.mode_key == ModeKey::Select {
+        app.switch_mode(ModeKey::Normal);
+    }
+    move_cursor(app)
+}
+
+// 从按下的位置开始拖动选择
+pub fn drag(app: &mut Application) -> Result<()> {
+    if app.mode_key != ModeKey::Select {
+        select::start(app)?;
+    }
+    move_cursor(app)
+}
+
+pub fn scroll_up(app: &mut Application) -> Result<()> {
+    if let Some(ref buffer) = app.workspace.current_buffer {
+        app.monitor.scroll_up(buffer, SCROLL_LINES);
+    }
+    Ok(())
+}
+
+pub fn scroll_down(app: &mut Application) -> Result<()> {
+    if let Some(ref buffer) = app.workspace.current_buffer {
+        // 最后一行保持在屏幕上
+        let offset = app.monitor.get_scroll_controller(buffer).line_offset();
+        let count = SCROLL_LINES.min(buffer.line_count().saturating_sub(offset + 1));
+        app.monitor.scroll_down(buffer, count);
+    }
+    Ok(())
+}
+
+/// 鼠标所在位置对应的buffer位置
+pub(super) fn mouse_position(app: &Application) -> Option<Position> {
+    let mouse = app.monitor.last_mouse?;
+    app.monitor.screen_map.buffer_position(Position {
+        line: mouse.row as usize,
+        offset: mouse.column as usize,
+    })
+}
+
+/// 鼠标位于目录树面板中时返回所在的行，面板获得焦点且没有与编辑区并排时占满屏幕
+pub(super) fn tree_panel_row(app: &Application) -> Result<Option<usize>> {
+    let mouse = match app.monitor.last_mouse {
+        Some(mouse) => mouse,
+        None => return Ok(None),
+    };
+    let (row, column) = (mouse.row as usize, mouse.column as usize);
+    let width = match app.monitor.tree_panel_width()? {
+        0 if matches!(app.mode_key, ModeKey::Workspace | ModeKey::TreeFilter) => {
+            app.monitor.width()?
+        }
+        width if app.workspace.current_buffer.is_some() => width,
+        _ => app.monitor.width()?,
+    };
+    // 最后一行是状态栏
+    if column < width && row + 1 < app.monitor.height()? {
+        Ok(Some(row))
+    } else {
+        Ok(None)
+    }
+}
+
+fn move_cursor(app: &mut Application) -> Result<()> {
+    if let (Some(position), Some(buffer)) =
+        (mouse_position(app), app.workspace.current_buffer.as_mut())
+    {
+        buffer.cursor.move_to(position);
+    }
+    Ok(())
+}
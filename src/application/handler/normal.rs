@@ -1,4 +1,4 @@
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent};
 use held_core::utils::position::Position;
 use held_core::utils::range::Range;
 use unicode_segmentation::UnicodeSegmentation;
@@ -7,14 +7,16 @@ use crate::application::mode::motion;
 use crate::application::Application;
 use crate::errors::*;
 
+/// 已弃用：计数在匹配按键之前由 `count_modes` 中的模式处理，
+/// 保留该命令以兼容绑定了 `num: normal::count_cmd` 的旧配置
 pub fn count_cmd(app: &mut Application) -> Result<()> {
-    if let Some(key) = app.monitor.last_key {
-        if let KeyCode::Char(ch) = key.code {
-            if let Some(digit) = ch.to_digit(10) {
-                let count = &mut app.cmd_counter;
-                *count *= 10;
-                *count += digit as usize;
-            }
+    if let Some(KeyEvent {
+        code: KeyCode::Char(ch),
+        ..
+    }) = app.monitor.last_key
+    {
+        if let Some(digit) = ch.to_digit(10) {
+            app.push_count_digit(digit);
         }
     }
     Ok(())
@@ -74,6 +76,12 @@ pub fn move_to_target_line(app: &mut Application) -> Result<()> {
     Ok(())
 }
 
+// 有计数时跳转到对应行，否则跳转到第一行
+pub fn move_to_first_line(app: &mut Application) -> Result<()> {
+    app.cmd_counter = app.cmd_counter.max(1);
+    move_to_target_line(app)
+}
+
 pub fn move_left_n(app: &mut Application) -> Result<()> {
     let mut count = app.cmd_counter.max(1);
     if let Some(buffer) = &mut app.workspace.current_buffer {
@@ -109,6 +117,10 @@ pub fn move_right_n(app: &mut Application) -> Result<()> {
     Ok(())
 }
 
+pub fn repeat_last_change(app: &mut Application) -> Result<()> {
+    app.repeat_last_change()
+}
+
 pub fn reset(app: &mut Application) -> Result<()> {
     app.cmd_counter = 0;
     Ok(())
@@ -0,0 +1,141 @@
+use crossterm::event::KeyCode;
+
+use crate::application::mode::prompt::{PromptAnswer, PromptKind};
+use crate::application::mode::ModeData;
+use crate::application::Application;
+use crate::errors::*;
+
+pub fn input_char(app: &mut Application) -> Result<()> {
+    let c = match app.monitor.last_key.map(|key| key.code) {
+        Some(KeyCode::Char(c)) => c,
+        _ => return Ok(()),
+    };
+
+    let answer = match app.mode {
+        ModeData::Prompt(ref mut prompt_data) => match prompt_data.kind {
+            PromptKind::Confirm(_) => prompt_data.option_key(c).map(PromptAnswer::Key),
+            PromptKind::Input {
+                ref mut input,
+                ref mut history_index,
+                ..
+            } => {
+                input.push(c);
+                *history_index = None;
+                None
+            }
+            PromptKind::Choice { .. } => None,
+        },
+        _ => None,
+    };
+    match answer {
+        Some(answer) => reply(app, answer),
+        None => Ok(()),
+    }
+}
+
+pub fn backspace(app: &mut Application) -> Result<()> {
+    if let ModeData::Prompt(ref mut prompt_data) = app.mode {
+        if let PromptKind::Input {
+            ref mut input,
+            ref mut history_index,
+            ..
+        } = prompt_data.kind
+        {
+            input.pop();
+            *history_index = None;
+        }
+    }
+    Ok(())
+}
+
+pub fn confirm(app: &mut Application) -> Result<()> {
+    let answer = match app.mode {
+        ModeData::Prompt(ref prompt_data) => match prompt_data.kind {
+            PromptKind::Input {
+                ref input, history, ..
+            } => {
+                let entries = app.prompt_history.entry(history).or_default();
+                if !input.is_empty() && entries.last() != Some(input) {
+                    entries.push(input.clone());
+                }
+                PromptAnswer::Text(input.clone())
+            }
+            PromptKind::Choice {
+                ref items,
+                selected,
+            } if !items.is_empty() => PromptAnswer::Choice(selected),
+            // 确认提示只接受选项的首字母
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+    reply(app, answer)
+}
+
+pub fn cancel(app: &mut Application) -> Result<()> {
+    reply(app, PromptAnswer::Cancel)
+}
+
+// 输入提示中查看上一条历史，列表中选择上一项
+pub fn previous(app: &mut Application) -> Result<()> {
+    move_selection(app, false);
+    Ok(())
+}
+
+pub fn next(app: &mut Application) -> Result<()> {
+    move_selection(app, true);
+    Ok(())
+}
+
+fn move_selection(app: &mut Application, forward: bool) {
+    let prompt_data = match app.mode {
+        ModeData::Prompt(ref mut prompt_data) => prompt_data,
+        _ => return,
+    };
+    match prompt_data.kind {
+        PromptKind::Input {
+            ref mut input,
+            history,
+            ref mut history_index,
+        } => {
+            let entries = app
+                .prompt_history
+                .get(history)
+                .map_or(&[][..], |entries| &entries[..]);
+            let index = match (*history_index, forward) {
+                (None, false) => entries.len().checked_sub(1),
+                // 正在编辑新的输入
+                (None, true) => return,
+                (Some(index), false) => Some(index.saturating_sub(1)),
+                (Some(index), true) => Some(index + 1).filter(|index| *index < entries.len()),
+            };
+            *history_index = index;
+            *input = index.map_or_else(String::new, |index| entries[index].clone());
+        }
+        PromptKind::Choice {
+            ref items,
+            ref mut selected,
+        } => {
+            *selected = match forward {
+                true => (*selected + 1).min(items.len().saturating_sub(1)),
+                false => selected.saturating_sub(1),
+            };
+        }
+        PromptKind::Confirm(_) => {}
+    }
+}
+
+// 回到打开提示之前的模式，再以回答调用提示的callback
+fn reply(app: &mut Application, answer: PromptAnswer) -> Result<()> {
+    let (callback, return_mode) = match app.mode {
+        ModeData::Prompt(ref mut prompt_data) => {
+            (prompt_data.callback.take(), prompt_data.return_mode)
+        }
+        _ => return Ok(()),
+    };
+    app.switch_mode(return_mode);
+    match callback {
+        Some(callback) => callback(app, answer),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs::read_to_string;
+
+use error_chain::bail;
+
+use super::{list, shell};
+use crate::application::Application;
+use crate::errors::*;
+use crate::modules::location::Location;
+use crate::modules::quickfix::parse_errors;
+
+/// `:copen` 在列表中浏览快速修复列表
+pub fn open(app: &mut Application) -> Result<()> {
+    if app.quickfix.items().is_empty() {
+        bail!("Quickfix list is empty");
+    }
+    list::show(app);
+    Ok(())
+}
+
+/// `:cnext` 跳转到下一项
+pub fn next(app: &mut Application) -> Result<()> {
+    let location = app.quickfix.next().cloned().ok_or("No more items")?;
+    jump(app, &location)
+}
+
+/// `:cprev` 跳转到上一项
+pub fn previous(app: &mut Application) -> Result<()> {
+    let location = app.quickfix.previous().cloned().ok_or("No previous item")?;
+    jump(app, &location)
+}
+
+/// `:cfile errfile` 从文件中读取编译错误等位置
+pub(super) fn load_file(app: &mut Application, path: &str) -> Result<()> {
+    if path.is_empty() {
+        bail!("No error file given");
+    }
+    let file = app.workspace.path.join(path);
+    let content =
+        read_to_string(&file).chain_err(|| format!("Couldn't read error file \"{}\"", path))?;
+    let items = parse_errors(&content, &app.workspace.path);
+    if items.is_empty() {
+        bail!("No errors found in \"{}\"", path);
+    }
+    app.quickfix.set(format!("cfile {path}"), items);
+    next(app)
+}
+
+/// `:make args` 在工作区中运行设置的构建命令，跳转到输出中的第一个错误
+pub(super) fn make(app: &mut Application, args: &str) -> Result<()> {
+    let command = format!("{} {}", app.perferences.borrow().make_command(), args);
+    let command = command.trim();
+    let (status, output) = shell::run_in(command, &app.workspace.path)?;
+    let items = parse_errors(&output, &app.workspace.path);
+    let found = !items.is_empty();
+    app.quickfix.set(command.to_string(), items);
+
+    if found {
+        next(app)
+    } else if !status.success() {
+        bail!("\"{}\" exited with {}", command, status);
+    } else {
+        app.messages
+            .info(format!("\"{command}\" finished without errors"));
+        Ok(())
+    }
+}
+
+// 跳转并在状态栏显示该项的序号与说明
+fn jump(app: &mut Application, location: &Location) -> Result<()> {
+    list::jump_to(app, location)?;
+    if let Some(index) = app.quickfix.current() {
+        app.messages.info(format!(
+            "({} of {}) {}",
+            index + 1,
+            app.quickfix.items().len(),
+            location.text.trim()
+        ));
+    }
+    Ok(())
+}
@@ -1,14 +1,17 @@
+use super::{cmdline, quickfix};
 use crate::application::mode::ModeData;
 use crate::application::Application;
 use crate::errors::*;
-use crossterm::event::KeyCode;
+use crate::modules::grep;
+use error_chain::bail;
 use held_core::utils::{position::Position, range::Range};
+use regex::Regex;
 
 pub fn exec_search(app: &mut Application) -> Result<()> {
     if let ModeData::Search(ref mut search_data) = app.mode {
         search_data.is_exec_search = true;
         if let Some(ref mut buffer) = app.workspace.current_buffer {
-            let search_string = search_data.search_string.clone();
+            let search_string = search_data.input.text().to_string();
             let search_result = buffer.search(&search_string);
 
             let fixed_offset = search_string.len();
@@ -25,34 +28,17 @@ pub fn exec_search(app: &mut Application) -> Result<()> {
 
             search_data.search_result = ranges;
         }
+        search_data.history.push(search_data.input.text())?;
     }
     Ok(())
 }
 
 pub fn input_search_data(app: &mut Application) -> Result<()> {
-    if let Some(key) = app.monitor.last_key {
-        if let KeyCode::Char(c) = key.code {
-            if let ModeData::Search(ref mut search_data) = app.mode {
-                if search_data.is_exec_search == false {
-                    search_data
-                        .search_string
-                        .insert(search_data.search_string.len(), c);
-                }
-            }
-        }
-    }
-    Ok(())
+    cmdline::insert_char(app)
 }
 
 pub fn backspace(app: &mut Application) -> Result<()> {
-    if let ModeData::Search(ref mut search_data) = app.mode {
-        if search_data.is_exec_search == false && search_data.search_string.len() > 0 {
-            search_data
-                .search_string
-                .remove(search_data.search_string.len() - 1);
-        }
-    }
-    Ok(())
+    cmdline::backspace(app)
 }
 
 pub fn last_result(app: &mut Application) -> Result<()> {
@@ -82,3 +68,19 @@ pub fn clear(app: &mut Application) -> Result<()> {
     }
     Ok(())
 }
+
+/// `:grep pattern` 在工作区的所有文件中查找正则表达式，结果放入快速修复列表并打开
+pub(super) fn grep(app: &mut Application, pattern: &str) -> Result<()> {
+    if pattern.is_empty() {
+        bail!("No pattern given");
+    }
+    let regex = Regex::new(pattern).chain_err(|| format!("Invalid pattern: {}", pattern))?;
+    let ignore_patterns = app.perferences.borrow().ignore_patterns();
+    let locations = grep::grep(&app.workspace.path, &ignore_patterns, &regex);
+    if locations.is_empty() {
+        app.messages.warning(format!("No matches for {pattern}"));
+        return Ok(());
+    }
+    app.quickfix.set(format!("grep {pattern}"), locations);
+    quickfix::open(app)
+}
@@ -0,0 +1,27 @@
+use crate::application::mode::{ModeData, ModeKey};
+use crate::application::Application;
+use crate::errors::*;
+
+// 以当前光标位置为锚点开始选择
+pub fn start(app: &mut Application) -> Result<()> {
+    if let Some(ref buffer) = app.workspace.current_buffer {
+        let anchor = *buffer.cursor;
+        app.switch_mode(ModeKey::Select);
+        if let ModeData::Select(ref mut select_data) = app.mode {
+            select_data.anchor = anchor;
+        }
+    }
+    Ok(())
+}
+
+pub fn delete(app: &mut Application) -> Result<()> {
+    if let (ModeData::Select(ref select_data), Some(buffer)) =
+        (&app.mode, app.workspace.current_buffer.as_mut())
+    {
+        let range = select_data.range(*buffer.cursor);
+        buffer.delete_range(range.clone());
+        buffer.cursor.move_to(range.start());
+    }
+    app.switch_mode(ModeKey::Normal);
+    Ok(())
+}
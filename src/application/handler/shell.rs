@@ -0,0 +1,210 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+
+use held_core::utils::position::Position;
+use held_core::utils::range::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::application::Application;
+use crate::errors::*;
+use error_chain::bail;
+
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+fn shell(command: &str) -> Command {
+    let shell = env::var("SHELL").unwrap_or_else(|_| DEFAULT_SHELL.to_string());
+    let mut shell = Command::new(shell);
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// `:!cmd` 交出终端运行命令，按回车后回到编辑器
+pub(super) fn run(app: &mut Application, command: &str) -> Result<()> {
+    if app.headless {
+        let status = shell(command)
+            .status()
+            .chain_err(|| format!("Failed to run \"{command}\""))?;
+        if !status.success() {
+            bail!("\"{command}\" exited with {}", status);
+        }
+        return Ok(());
+    }
+
+    app.monitor.suspend();
+    let status = shell(command).status();
+    match status {
+        Ok(ref status) if !status.success() => println!("\n\"{command}\" exited with {status}"),
+        Err(ref e) => println!("\nFailed to run \"{command}\": {e}"),
+        _ => {}
+    }
+    print!("\nPress ENTER to continue");
+    let _ = io::stdout().flush();
+    let _ = io::stdin().lock().read_line(&mut String::new());
+    app.monitor.resume()
+}
+
+/// `:r !cmd` 将命令的输出插入到光标所在行之下
+pub(super) fn read(app: &mut Application, command: &str) -> Result<()> {
+    let output = capture(command, None)?;
+    let output = output.strip_suffix('\n').unwrap_or(&output);
+    if let Some(buffer) = app.workspace.current_buffer.as_mut() {
+        let line = buffer.cursor.line;
+        let line_end = buffer
+            .data()
+            .lines()
+            .nth(line)
+            .map_or(0, |content| content.graphemes(true).count());
+        buffer.cursor.move_to(Position::new(line, line_end));
+        buffer.insert(format!("\n{output}"));
+        buffer.cursor.move_to(Position::new(line + 1, 0));
+    }
+    Ok(())
+}
+
+/// `:{range}!filter` 将范围内的行交给命令处理，并替换为命令的输出
+pub(super) fn filter(app: &mut Application, range: &str, command: &str) -> Result<()> {
+    let buffer = match app.workspace.current_buffer.as_mut() {
+        Some(buffer) => buffer,
+        None => return Ok(()),
+    };
+    let data = buffer.data();
+    let lines = buffer_lines(&data);
+    let (start, end) = parse_line_range(range, buffer.cursor.line, lines.len())
+        .ok_or_else(|| format!("Invalid range \"{range}\""))?;
+
+    let input = lines[start..=end].join("\n") + "\n";
+    let output = capture(command, Some(input))?;
+    let output = output.strip_suffix('\n').unwrap_or(&output);
+
+    // 删除与插入作为一次撤销
+    buffer.start_operation_group();
+    buffer.delete_range(Range::new(
+        Position::new(start, 0),
+        Position::new(end, lines[end].graphemes(true).count()),
+    ));
+    buffer.cursor.move_to(Position::new(start, 0));
+    buffer.insert(output);
+    buffer.end_operation_group();
+    buffer.cursor.move_to(Position::new(start, 0));
+    Ok(())
+}
+
+// 运行命令并返回标准输出，命令失败时以标准错误作为错误信息
+fn capture(command: &str, input: Option<String>) -> Result<String> {
+    let mut child = shell(command)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("Failed to run \"{command}\""))?;
+
+    // 另起线程写入，避免输出填满管道时互相等待
+    let writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => {
+            Some(thread::spawn(move || stdin.write_all(input.as_bytes())))
+        }
+        _ => None,
+    };
+    let output = child
+        .wait_with_output()
+        .chain_err(|| format!("Failed to run \"{command}\""))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        if stderr.is_empty() {
+            bail!("\"{command}\" exited with {}", output.status);
+        }
+        bail!("\"{command}\" exited with {}: {stderr}", output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 在dir中运行命令，返回退出状态以及标准输出与标准错误的内容
+pub(super) fn run_in(command: &str, dir: &Path) -> Result<(ExitStatus, String)> {
+    let output = shell(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .chain_err(|| format!("Failed to run \"{command}\""))?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status, text))
+}
+
+/// 解析行范围，返回从0开始的首尾行号
+///
+/// 支持 `%`、`.`、`$`、行号以及以逗号分隔的两端，如 `1,5`、`.,$`。
+fn parse_line_range(range: &str, current: usize, line_count: usize) -> Option<(usize, usize)> {
+    let last = line_count.max(1) - 1;
+    let address = |address: &str| match address {
+        "." => Some(current),
+        "$" => Some(last),
+        number => number
+            .parse::<usize>()
+            .ok()
+            .filter(|number| *number > 0)
+            .map(|number| (number - 1).min(last)),
+    };
+
+    let (start, end) = match range {
+        "%" => (0, last),
+        range => match range.split_once(',') {
+            Some((start, end)) => (address(start)?, address(end)?),
+            None => (address(range)?, address(range)?),
+        },
+    };
+    Some((start.min(end).min(last), start.max(end).min(last)))
+}
+
+// buffer中的各行，空buffer视为一个空行
+fn buffer_lines(data: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = data.lines().collect();
+    if lines.is_empty() {
+        lines.push("");
+    }
+    lines
+}
+
+/// 将 `{range}!cmd` 拆分为范围与命令
+pub(super) fn split_filter(cmd: &str) -> Option<(&str, &str)> {
+    let (range, command) = cmd.split_once('!')?;
+    let valid = !range.is_empty()
+        && range
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '%' | '.' | '$' | ','));
+    valid.then_some((range, command.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{buffer_lines, parse_line_range, split_filter};
+
+    #[test]
+    fn line_ranges_are_clamped_to_the_buffer() {
+        assert_eq!(parse_line_range("%", 3, 10), Some((0, 9)));
+        assert_eq!(parse_line_range(".", 3, 10), Some((3, 3)));
+        assert_eq!(parse_line_range(".,$", 3, 10), Some((3, 9)));
+        assert_eq!(parse_line_range("5,2", 0, 10), Some((1, 4)));
+        assert_eq!(parse_line_range("1,99", 0, 10), Some((0, 9)));
+        assert_eq!(parse_line_range("0", 0, 10), None);
+        // 空buffer视为一个空行，范围不会越界
+        let lines = buffer_lines("");
+        assert_eq!(lines, vec![""]);
+        assert_eq!(parse_line_range("%", 0, lines.len()), Some((0, 0)));
+        assert_eq!(parse_line_range(".", 1, lines.len()), Some((0, 0)));
+        assert_eq!(split_filter("%!sort -r"), Some(("%", "sort -r")));
+        assert_eq!(split_filter("!ls"), None);
+        assert_eq!(split_filter("r !ls"), None);
+    }
+}
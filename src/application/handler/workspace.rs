@@ -1,38 +1,370 @@
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use error_chain::bail;
+use walkdir::WalkDir;
+
+use crate::application::mode::prompt::{PromptAnswer, PromptData};
 use crate::application::mode::{ModeData, ModeKey};
 use crate::application::Application;
 use crate::errors::*;
 
+use super::buffer::PATH_HISTORY;
+use super::mouse;
+
+/// 焦点回到编辑区，面板保持显示；没有打开的文件时留在目录树中
 pub fn to_normal_mode(app: &mut Application) -> Result<()> {
-    if let ModeData::Workspace(ref mode) = app.mode {
-        if let Some(buffer_id) = mode.prev_buffer_id {
-            app.workspace.select_buffer(buffer_id);
-        } else {
+    if app.workspace.current_buffer.is_some() {
+        app.switch_mode(ModeKey::Normal);
+    }
+    Ok(())
+}
+
+/// 显示或隐藏目录树面板，不改变焦点
+pub fn toggle(app: &mut Application) -> Result<()> {
+    if app.monitor.tree_panel.is_visible() {
+        close(app)
+    } else {
+        app.monitor.tree_panel.set_visible(true);
+        Ok(())
+    }
+}
+
+/// 隐藏目录树面板，焦点回到编辑区
+pub fn close(app: &mut Application) -> Result<()> {
+    if app.workspace.current_buffer.is_none() {
+        return Ok(());
+    }
+    app.monitor.tree_panel.set_visible(false);
+    to_normal_mode(app)
+}
+
+pub fn move_down(app: &mut Application) -> Result<()> {
+    app.monitor.tree_panel.tree.move_selection(true);
+    Ok(())
+}
+
+pub fn move_up(app: &mut Application) -> Result<()> {
+    app.monitor.tree_panel.tree.move_selection(false);
+    Ok(())
+}
+
+/// 展开或收起所选的目录，所选的是文件时打开并将焦点交给编辑区
+pub fn enter(app: &mut Application) -> Result<()> {
+    if let Some(path) = app.monitor.tree_panel.tree.toggle_selected() {
+        app.workspace
+            .open_buffer(&path, &mut app.monitor)
+            .chain_err(|| format!("Couldn't open \"{}\"", path.to_string_lossy()))?;
+        app.switch_mode(ModeKey::Normal);
+    }
+    Ok(())
+}
+
+// 点击目录树中的节点：打开文件，或展开/收起目录并将焦点交给面板；
+// 面板获得焦点时点击编辑区则焦点回到编辑区
+pub fn click(app: &mut Application) -> Result<()> {
+    let row = match mouse::tree_panel_row(app)? {
+        Some(row) => row,
+        None => {
+            if app.workspace.current_buffer.is_some() {
+                app.switch_mode(ModeKey::Normal);
+                return mouse::click(app);
+            }
             return Ok(());
         }
+    };
+    let index = app.monitor.tree_panel.entry_at(row);
+    if app.monitor.tree_panel.tree.select(index) {
+        app.switch_mode(ModeKey::Workspace);
+        return enter(app);
     }
-    app.switch_mode(ModeKey::Normal);
     Ok(())
 }
 
-pub fn move_down(app: &mut Application) -> Result<()> {
-    if let ModeData::Workspace(ref mut mode) = app.mode {
-        mode.move_down();
+/// 在状态栏输入过滤目录树，只显示文件名包含输入的文件
+pub fn filter(app: &mut Application) -> Result<()> {
+    let query = app.monitor.tree_panel.tree.query().to_string();
+    app.switch_mode(ModeKey::TreeFilter);
+    if let ModeData::TreeFilter(ref mut input) = app.mode {
+        input.set(query);
     }
     Ok(())
 }
 
-pub fn move_up(app: &mut Application) -> Result<()> {
-    if let ModeData::Workspace(ref mut mode) = app.mode {
-        mode.move_up();
+/// 保留过滤结果，回到目录树中选择
+pub fn confirm_filter(app: &mut Application) -> Result<()> {
+    app.switch_mode(ModeKey::Workspace);
+    Ok(())
+}
+
+/// 清除过滤，显示完整的目录树
+pub fn clear_filter(app: &mut Application) -> Result<()> {
+    app.monitor.tree_panel.tree.set_query("");
+    app.switch_mode(ModeKey::Workspace);
+    Ok(())
+}
+
+/// 切换是否显示以 `.` 开头的文件
+pub fn toggle_hidden(app: &mut Application) -> Result<()> {
+    let show_hidden = !app.perferences.borrow().tree_show_hidden();
+    app.perferences
+        .borrow_mut()
+        .set("tree.hidden", &show_hidden.to_string())?;
+    app.messages.info(if show_hidden {
+        "Showing hidden files"
+    } else {
+        "Hiding hidden files"
+    });
+    Ok(())
+}
+
+/// 在所选目录（或所选文件所在的目录）中新建文件，输入以 `/` 结尾时新建目录
+pub fn create(app: &mut Application) -> Result<()> {
+    let (root, selected) = selection(app);
+    let dir = if selected.is_dir() {
+        selected
+    } else {
+        selected.parent().unwrap_or(&root).to_path_buf()
+    };
+
+    app.prompt(PromptData::input(
+        format!(
+            "New file in {}/ (end with / for a directory):",
+            relative(&root, &dir)
+        ),
+        PATH_HISTORY,
+        move |app, answer| match answer {
+            PromptAnswer::Text(name) if !name.trim().is_empty() => {
+                create_path(app, dir.join(name.trim()), name.trim().ends_with('/'))
+            }
+            _ => Ok(()),
+        },
+    ));
+    Ok(())
+}
+
+/// 在同一目录中修改所选节点的名字
+pub fn rename(app: &mut Application) -> Result<()> {
+    let (root, selected) = selection(app);
+    if selected == root {
+        bail!("Can't rename the workspace root");
     }
+    let name = selected
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    app.prompt(
+        PromptData::input(
+            format!("Rename {} to:", relative(&root, &selected)),
+            PATH_HISTORY,
+            move |app, answer| match answer {
+                PromptAnswer::Text(name) if !name.trim().is_empty() => {
+                    let target = selected.with_file_name(name.trim());
+                    move_path(app, selected, target)
+                }
+                _ => Ok(()),
+            },
+        )
+        .with_input(name),
+    );
     Ok(())
 }
 
-pub fn enter(app: &mut Application) -> Result<()> {
-    if let ModeData::Workspace(ref mut mode) = app.mode {
-        if mode.open(&mut app.workspace, &mut app.monitor)? {
-            to_normal_mode(app)?;
+/// 将所选节点移动到相对于工作区的路径，目标为已有目录时移动到该目录中
+pub fn move_to(app: &mut Application) -> Result<()> {
+    transfer(app, "Move", move_path)
+}
+
+/// 将所选节点复制到相对于工作区的路径，目录连同其中的内容一起复制
+pub fn copy_to(app: &mut Application) -> Result<()> {
+    transfer(app, "Copy", copy_path)
+}
+
+/// 确认后删除所选节点，目录连同其中的内容一起删除
+pub fn delete(app: &mut Application) -> Result<()> {
+    let (root, selected) = selection(app);
+    if selected == root {
+        bail!("Can't delete the workspace root");
+    }
+
+    let message = if selected.is_dir() {
+        format!(
+            "Delete \"{}\" and everything in it?",
+            relative(&root, &selected)
+        )
+    } else {
+        format!("Delete \"{}\"?", relative(&root, &selected))
+    };
+    app.prompt(PromptData::confirm(
+        message,
+        &["yes", "no"],
+        move |app, answer| match answer {
+            PromptAnswer::Key('y') => delete_path(app, &selected),
+            _ => Ok(()),
+        },
+    ));
+    Ok(())
+}
+
+/// 重新读取目录树，丢弃已不存在的目录的展开状态
+pub fn refresh(app: &mut Application) -> Result<()> {
+    app.monitor.tree_panel.tree.refresh();
+    Ok(())
+}
+
+/// 展开并选择正在编辑的文件
+pub fn reveal_current_file(app: &mut Application) -> Result<()> {
+    let path = app
+        .workspace
+        .current_buffer
+        .as_ref()
+        .and_then(|buffer| buffer.path.clone())
+        .ok_or("No file to reveal")?;
+    reveal(app, &path);
+    Ok(())
+}
+
+// 工作区的根目录与所选的节点
+fn selection(app: &Application) -> (PathBuf, PathBuf) {
+    let tree = &app.monitor.tree_panel.tree;
+    (
+        tree.root().to_path_buf(),
+        tree.selected_path().to_path_buf(),
+    )
+}
+
+// 相对于工作区根目录的路径，根目录显示为 `.`
+fn relative(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+// 询问目标路径后以operation移动或复制所选节点
+fn transfer(
+    app: &mut Application,
+    verb: &str,
+    operation: fn(&mut Application, PathBuf, PathBuf) -> Result<()>,
+) -> Result<()> {
+    let (root, selected) = selection(app);
+    if selected == root {
+        bail!("Can't {} the workspace root", verb.to_lowercase());
+    }
+    let path = relative(&root, &selected);
+
+    app.prompt(
+        PromptData::input(
+            format!("{verb} {path} to:"),
+            PATH_HISTORY,
+            move |app, answer| match answer {
+                PromptAnswer::Text(target) if !target.trim().is_empty() => {
+                    let mut target = root.join(target.trim());
+                    if target.is_dir() {
+                        if let Some(name) = selected.file_name() {
+                            target.push(name);
+                        }
+                    }
+                    operation(app, selected, target)
+                }
+                _ => Ok(()),
+            },
+        )
+        .with_input(path.clone()),
+    );
+    Ok(())
+}
+
+fn create_path(app: &mut Application, path: PathBuf, is_dir: bool) -> Result<()> {
+    if path.exists() {
+        bail!("\"{}\" already exists", path.to_string_lossy());
+    }
+    if is_dir {
+        fs::create_dir_all(&path)
+            .chain_err(|| format!("Couldn't create \"{}\"", path.to_string_lossy()))?;
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .chain_err(|| format!("Couldn't create \"{}\"", parent.to_string_lossy()))?;
+        }
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .chain_err(|| format!("Couldn't create \"{}\"", path.to_string_lossy()))?;
+    }
+    reveal(app, &path);
+    Ok(())
+}
+
+// 移动或重命名，已打开的文件的buffer随之指向新的路径
+fn move_path(app: &mut Application, from: PathBuf, to: PathBuf) -> Result<()> {
+    check_target(&from, &to)?;
+    fs::rename(&from, &to).chain_err(|| {
+        format!(
+            "Couldn't move \"{}\" to \"{}\"",
+            from.to_string_lossy(),
+            to.to_string_lossy()
+        )
+    })?;
+    app.workspace.repoint_buffers(&from, &to);
+    reveal(app, &to);
+    Ok(())
+}
+
+fn copy_path(app: &mut Application, from: PathBuf, to: PathBuf) -> Result<()> {
+    check_target(&from, &to)?;
+    let error = || {
+        format!(
+            "Couldn't copy \"{}\" to \"{}\"",
+            from.to_string_lossy(),
+            to.to_string_lossy()
+        )
+    };
+    if from.is_dir() {
+        for entry in WalkDir::new(&from) {
+            let entry = entry.chain_err(error)?;
+            let target = to.join(entry.path().strip_prefix(&from).unwrap_or(entry.path()));
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&target).chain_err(error)?;
+            } else {
+                fs::copy(entry.path(), &target).chain_err(error)?;
+            }
         }
+    } else {
+        fs::copy(&from, &to).chain_err(error)?;
     }
+    reveal(app, &to);
     Ok(())
 }
+
+// 目标不能已经存在，目录不能移动或复制到自身之中；缺少的上级目录会被创建
+fn check_target(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        bail!("\"{}\" already exists", to.to_string_lossy());
+    }
+    if to.starts_with(from) {
+        bail!("Can't move or copy a directory into itself");
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .chain_err(|| format!("Couldn't create \"{}\"", parent.to_string_lossy()))?;
+    }
+    Ok(())
+}
+
+fn delete_path(app: &mut Application, path: &Path) -> Result<()> {
+    app.workspace.forget_files(path);
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.chain_err(|| format!("Couldn't delete \"{}\"", path.to_string_lossy()))
+}
+
+fn reveal(app: &mut Application, path: &Path) {
+    app.monitor.tree_panel.tree.reveal(path);
+}
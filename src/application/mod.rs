@@ -1,22 +1,42 @@
 use crate::{
     errors::*,
-    modules::input::{InputLoader, InputMapper},
+    modules::{
+        cmdline::{InputHistory, LineEditor},
+        file_tree::FileTree,
+        input::{
+            keymap::{KeyAction, KeyTrie, FALLBACK_KEY},
+            InputLoader, InputMapper,
+        },
+        macros::{MacroPending, Macros},
+        messages::Messages,
+        quickfix::Quickfix,
+        repeat::Repeat,
+    },
     plugin::system::PluginSystem,
 };
-use crossterm::{event::Event, terminal::disable_raw_mode};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use error_chain::bail;
 use held_core::plugin::Plugin;
 use mode::{
-    command::CommandData, error::ErrorRenderer, search::SearchData, workspace::WorkspaceModeData,
-    ModeData, ModeKey, ModeRenderer, ModeRouter,
+    command::CommandData, error::ErrorRenderer, finder::FinderData, list::ListData,
+    prompt::PromptData, search::SearchData, select::SelectData, ModeData, ModeKey, ModeRenderer,
+    ModeRouter,
 };
-use smallvec::SmallVec;
 use state::ApplicationStateData;
 
-use std::{cell::RefCell, collections::HashMap, mem, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    mem,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use crate::{
     modules::perferences::{Perferences, PerferencesManager},
-    view::monitor::Monitor,
+    view::{
+        monitor::Monitor, terminal::headless_terminal::HeadlessTerminal, tree_panel::TreePanel,
+    },
     workspace::Workspace,
 };
 
@@ -25,48 +45,116 @@ pub mod mode;
 pub mod plugin_interafce;
 pub mod state;
 
+// 宏中可以回放其他宏，限制嵌套层数以避免自我调用
+const MAX_MACRO_DEPTH: usize = 64;
+// 等待后台任务时检查结果的间隔
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 命令与搜索输入历史的文件名
+const COMMAND_HISTORY: &str = "command";
+const SEARCH_HISTORY: &str = "search";
+
 pub struct Application {
     pub workspace: Workspace,
     pub monitor: Monitor,
     pub perferences: Rc<RefCell<dyn Perferences>>,
     pub mode: ModeData,
     mode_key: ModeKey,
+    // 切换到当前模式之前的模式，当前模式无法渲染时回到该模式
+    previous_mode_key: ModeKey,
     mode_history: HashMap<ModeKey, ModeData>,
-    input_map: HashMap<
-        String,
-        HashMap<
-            String,
-            SmallVec<[fn(&mut crate::Application) -> std::result::Result<(), Error>; 4]>,
-        >,
-    >,
+    // 各模式的按键序列前缀树
+    keymaps: HashMap<String, KeyTrie>,
+    // 已输入但尚未匹配完成的按键序列
+    pending_keys: Vec<(String, KeyEvent)>,
+    pending_deadline: Option<Instant>,
+    key_timeout: Duration,
+    count_modes: Vec<String>,
+    leader: String,
     plugin_system: Rc<RefCell<PluginSystem>>,
     pub state_data: ApplicationStateData,
     pub cmd_counter: usize,
+    pub macros: Macros,
+    repeat: Repeat,
+    // 插入模式下上一次按键的时间，用于按停顿拆分撤销单元
+    last_edit_input: Option<Instant>,
+    // 无名寄存器，Normal模式下粘贴的文本保存在这里
+    pub register: Option<String>,
+    // 无界面模式下没有可以交互的终端
+    pub headless: bool,
+    pub messages: Messages,
+    // 各类输入提示的历史记录
+    pub prompt_history: HashMap<&'static str, Vec<String>>,
+    // grep结果、编译错误等位置的列表
+    pub quickfix: Quickfix,
 }
 
 impl Application {
     pub fn new(args: &[String]) -> Result<Self> {
+        Self::create(args, false)
+    }
+
+    /// 不接管终端的无界面实例，用于执行脚本
+    pub fn headless(args: &[String]) -> Result<Self> {
+        Self::create(args, true)
+    }
+
+    fn create(args: &[String], headless: bool) -> Result<Self> {
         let perferences = PerferencesManager::load()?;
 
         let plugin_system = Rc::new(RefCell::new(PluginSystem::init_system(
             perferences.borrow().plugins_path()?,
         )));
 
-        let input_map = InputLoader::load(perferences.borrow().input_config_path()?)?;
-        let mut monitor = Monitor::new(perferences.clone(), plugin_system.clone())?;
+        let input_config = InputLoader::load(perferences.borrow().input_config_path()?)?;
+        let macros = Macros::load(perferences.borrow().input_config_path()?)?;
+        let mut monitor = if headless {
+            Monitor::with_terminal(
+                perferences.clone(),
+                plugin_system.clone(),
+                Box::new(HeadlessTerminal),
+            )?
+        } else {
+            Monitor::new(perferences.clone(), plugin_system.clone())?
+        };
         let workspace = Workspace::create_workspace(&mut monitor, perferences.borrow(), args)?;
 
+        let mut messages = Messages::default();
+        for error in input_config.errors.iter() {
+            messages.warning(error.clone());
+        }
+        if let Some(theme_name) = perferences.borrow().theme_name() {
+            if monitor.get_theme(&theme_name).is_none() {
+                messages.warning(format!(
+                    "Couldn't find \"{theme_name}\" theme, using the default theme"
+                ));
+            }
+        }
+
         Ok(Self {
             workspace,
             monitor,
             perferences,
             mode: ModeData::Normal,
             mode_key: ModeKey::Normal,
+            previous_mode_key: ModeKey::Normal,
             mode_history: HashMap::new(),
-            input_map,
+            keymaps: input_config.keymaps,
+            pending_keys: Vec::new(),
+            pending_deadline: None,
+            key_timeout: input_config.timeout,
+            count_modes: input_config.count_modes,
+            leader: input_config.leader,
             plugin_system,
             state_data: ApplicationStateData::default(),
             cmd_counter: 0,
+            macros,
+            repeat: Repeat::default(),
+            last_edit_input: None,
+            register: None,
+            headless,
+            messages,
+            prompt_history: HashMap::new(),
+            quickfix: Quickfix::default(),
         })
     }
 
@@ -77,27 +165,49 @@ impl Application {
     }
 
     fn init_modes(&mut self) -> Result<()> {
+        let history_path = self.perferences.borrow().history_path()?;
         self.mode_history.insert(ModeKey::Normal, ModeData::Normal);
         self.mode_history.insert(ModeKey::Insert, ModeData::Insert);
-        self.mode_history
-            .insert(ModeKey::Command, ModeData::Command(CommandData::new()));
+        self.mode_history.insert(
+            ModeKey::Command,
+            ModeData::Command(CommandData::new(InputHistory::load(
+                history_path.clone(),
+                COMMAND_HISTORY,
+            )?)),
+        );
         self.mode_history
             .insert(ModeKey::Replace, ModeData::Replace);
         self.mode_history
             .insert(ModeKey::Error, ModeData::Error(Error::default()));
         self.mode_history.insert(ModeKey::Exit, ModeData::Exit);
-        self.mode_history.insert(
-            ModeKey::Workspace,
-            ModeData::Workspace(WorkspaceModeData::new(
-                &mut self.workspace,
-                &mut self.monitor,
-            )?),
-        );
+        self.mode_history
+            .insert(ModeKey::Workspace, ModeData::Workspace);
         self.mode_history.insert(ModeKey::Delete, ModeData::Delete);
         self.mode_history
-            .insert(ModeKey::Search, ModeData::Search(SearchData::new()));
+            .insert(ModeKey::Select, ModeData::Select(SelectData::default()));
+        self.mode_history
+            .insert(ModeKey::Prompt, ModeData::Prompt(PromptData::default()));
+        self.mode_history
+            .insert(ModeKey::Finder, ModeData::Finder(FinderData::default()));
+        self.mode_history
+            .insert(ModeKey::List, ModeData::List(ListData::default()));
+        self.mode_history.insert(
+            ModeKey::TreeFilter,
+            ModeData::TreeFilter(LineEditor::default()),
+        );
+        self.mode_history.insert(
+            ModeKey::Search,
+            ModeData::Search(SearchData::new(InputHistory::load(
+                history_path,
+                SEARCH_HISTORY,
+            )?)),
+        );
 
+        let ignore_patterns = self.perferences.borrow().ignore_patterns();
+        self.monitor.tree_panel =
+            TreePanel::new(FileTree::new(&self.workspace.path, ignore_patterns));
         if self.workspace.current_buffer.is_none() {
+            self.monitor.tree_panel.set_visible(true);
             self.switch_mode(ModeKey::Workspace);
         }
 
@@ -111,67 +221,585 @@ impl Application {
             self.listen_event()?;
 
             if let ModeKey::Exit = &self.mode_key {
-                disable_raw_mode()?;
+                self.monitor.terminal.suspend();
                 return Ok(());
             }
         }
     }
 
+    /// 无界面模式：依次执行命令，返回进程退出码
+    ///
+    /// 命令不存在或执行失败时立即停止并返回1；
+    /// 脚本结束时若仍有未保存的修改同样返回1，与 `:q` 的检查保持一致。
+    pub fn run_script(&mut self, commands: &[String]) -> Result<i32> {
+        self.init()?;
+        for command in commands {
+            let command = command.trim_start_matches(':');
+            match handler::command::execute(self, command) {
+                Ok(true) => {}
+                Ok(false) => {
+                    eprintln!("held: Not an editor command: {}", command);
+                    return Ok(1);
+                }
+                Err(e) => {
+                    eprintln!("held: {}: {}", command, e);
+                    return Ok(1);
+                }
+            }
+
+            if self.mode_key == ModeKey::Exit {
+                return Ok(0);
+            }
+        }
+
+        if let Some(names) = self.workspace.modified_buffer_names() {
+            eprintln!("held: No write since last change: {}", names);
+            return Ok(1);
+        }
+        Ok(0)
+    }
+
     fn listen_event(&mut self) -> Result<()> {
+        // 多键序列等待后续按键，超时后按已输入的部分执行
+        if let Some(deadline) = self.pending_deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if !self.monitor.poll(timeout)? {
+                if let Err(e) = self.resolve_pending_keys(true) {
+                    self.messages.error(&e);
+                }
+                self.finish_change();
+                return Ok(());
+            }
+        }
+
+        // 目录树在后台读取时定时重绘，以显示读取的结果
+        if self.pending_deadline.is_none()
+            && self.monitor.tree_panel.is_busy()
+            && !self.monitor.poll(BACKGROUND_POLL_INTERVAL)?
+        {
+            return Ok(());
+        }
+
         let event = self.monitor.listen()?;
-        self.handle_input(event)?;
+        if let Event::Resize(..) = event {
+            return self.resize();
+        }
+        // 按键关闭当前显示的消息，处理按键时的错误显示在状态栏上而不退出
+        if let Event::Key(_) = event {
+            self.messages.dismiss();
+        }
+        if let Err(e) = self.handle_input(event) {
+            self.messages.error(&e);
+        }
+        Ok(())
+    }
+
+    // 终端大小改变时重绘整个屏幕，并保持光标在显示区域内
+    fn resize(&mut self) -> Result<()> {
+        self.monitor.redraw()?;
+        if let Some(ref buffer) = self.workspace.current_buffer {
+            self.monitor.scroll_to_cursor(buffer)?;
+        }
         Ok(())
     }
 
     fn render(&mut self) -> Result<()> {
-        if let Err(err) = ModeRouter::render(&mut self.workspace, &mut self.monitor, &mut self.mode)
-        {
-            ErrorRenderer::render(
-                &mut self.workspace,
-                &mut self.monitor,
-                &mut ModeData::Error(err),
-            )?;
+        self.monitor.message = self.messages.current().cloned();
+        let height = self.monitor.height()? - 1;
+        let focused = matches!(self.mode_key, ModeKey::Workspace | ModeKey::TreeFilter);
+        self.monitor.tree_panel.sync(
+            &self.workspace,
+            &*self.perferences.borrow(),
+            focused,
+            height,
+        );
+        let err = match ModeRouter::render(&mut self.workspace, &mut self.monitor, &mut self.mode) {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        self.messages.error(&err);
+        self.monitor.message = self.messages.current().cloned();
+
+        // 当前模式无法渲染时回到上一个模式
+        let previous = match self.previous_mode_key {
+            ModeKey::Exit | ModeKey::Error => ModeKey::Normal,
+            previous => previous,
+        };
+        if previous != self.mode_key {
+            self.switch_mode(previous);
+            if ModeRouter::render(&mut self.workspace, &mut self.monitor, &mut self.mode).is_ok() {
+                return Ok(());
+            }
+        }
+        ErrorRenderer::render(
+            &mut self.workspace,
+            &mut self.monitor,
+            &mut ModeData::Error(err),
+        )
+    }
+
+    /// 运行时绑定按键序列
+    pub fn map_keys(&mut self, mode: ModeKey, keys: &[String], action: KeyAction) -> Result<()> {
+        self.keymap_mut(mode)?.insert(keys, action);
+        Ok(())
+    }
+
+    pub fn unmap_keys(&mut self, mode: ModeKey, keys: &[String]) -> Result<()> {
+        if !self.keymap_mut(mode)?.remove(keys) {
+            bail!("No such mapping");
         }
         Ok(())
     }
 
+    fn keymap_mut(&mut self, mode: ModeKey) -> Result<&mut KeyTrie> {
+        mode.to_string()
+            .and_then(|mode| self.keymaps.get_mut(&mode))
+            .ok_or_else(|| format!("Mode {:?} has no key bindings", mode).into())
+    }
+
+    pub fn keymaps(&self) -> &HashMap<String, KeyTrie> {
+        &self.keymaps
+    }
+
+    pub fn leader(&self) -> &str {
+        &self.leader
+    }
+
+    fn resolve_macro_pending(&mut self, pending: MacroPending, key_event: KeyEvent) -> Result<()> {
+        let count = self.cmd_counter.max(1);
+        self.cmd_counter = 0;
+
+        let register = match key_event.code {
+            KeyCode::Char(c) => c,
+            // 其他按键（例如escape）取消
+            _ => return Ok(()),
+        };
+
+        match pending {
+            MacroPending::Record => {
+                if Macros::register_of(&register.to_string()).is_some() {
+                    self.macros.start_recording(register);
+                }
+                Ok(())
+            }
+            MacroPending::Play => {
+                let register = if register == '@' {
+                    match self.macros.last_played {
+                        Some(register) => register,
+                        None => return Ok(()),
+                    }
+                } else {
+                    register
+                };
+                self.play_macro(register, count)
+            }
+        }
+    }
+
+    /// 将宏中的按键重新送入 `handle_input`，回放期间产生的按键不会被录制
+    pub fn play_macro(&mut self, register: char, count: usize) -> Result<()> {
+        let keys = match self.macros.get(register) {
+            Some(keys) => keys.clone(),
+            None => return Ok(()),
+        };
+        if self.macros.depth >= MAX_MACRO_DEPTH {
+            bail!("Macro recursion is too deep");
+        }
+
+        self.macros.last_played = Some(register);
+        self.macros.depth += 1;
+        let result = self.replay_keys(&keys, count);
+        self.macros.depth -= 1;
+        result
+    }
+
+    fn replay_keys(&mut self, keys: &[String], count: usize) -> Result<()> {
+        for _ in 0..count {
+            for key in keys {
+                let event = InputMapper::str_map_event(key)
+                    .ok_or_else(|| format!("Invalid key \"{}\" in macro", key))?;
+                match event {
+                    Event::Key(key_event) => self.monitor.last_key = Some(key_event),
+                    Event::Paste(ref text) => self.monitor.last_paste = Some(text.clone()),
+                    _ => {}
+                }
+                self.handle_input(event)?;
+
+                if self.mode_key == ModeKey::Exit {
+                    return Ok(());
+                }
+            }
+        }
+
+        // 回放结束时不再等待后续按键
+        if !self.pending_keys.is_empty() {
+            self.resolve_pending_keys(true)?;
+        }
+        Ok(())
+    }
+
+    /// 切换到提示模式向用户提问，回答后回到当前模式再调用提示的callback
+    pub fn prompt(&mut self, mut prompt_data: PromptData) {
+        prompt_data.return_mode = match self.mode_key {
+            ModeKey::Command | ModeKey::Prompt => ModeKey::Normal,
+            mode_key => mode_key,
+        };
+        self.switch_mode(ModeKey::Prompt);
+        self.mode = ModeData::Prompt(prompt_data);
+    }
+
     pub fn switch_mode(&mut self, mode_key: ModeKey) {
         if self.mode_key == mode_key {
             return;
         }
 
+        // 一次插入/替换会话中的修改作为一个撤销单元
+        match (self.mode_key.is_editing(), mode_key.is_editing()) {
+            (false, true) => {
+                if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+                    buffer.start_operation_group();
+                }
+                self.last_edit_input = None;
+            }
+            (true, false) => {
+                if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+                    buffer.end_operation_group();
+                }
+            }
+            _ => {}
+        }
+
         let mut mode = self.mode_history.remove(&mode_key).unwrap();
 
         mem::swap(&mut self.mode, &mut mode);
 
         self.mode_history.insert(self.mode_key, mode);
 
+        self.previous_mode_key = self.mode_key;
         self.mode_key = mode_key;
     }
 
+    // 插入模式下的停顿与光标跳转都会拆分撤销单元
+    fn dispatch_edit_input(&mut self, event: Event) -> Result<()> {
+        let now = Instant::now();
+        let pause = self.perferences.borrow().undo_break_pause();
+        let paused = match (pause, self.last_edit_input) {
+            (Some(pause), Some(last)) => now.duration_since(last) >= pause,
+            _ => false,
+        };
+        self.last_edit_input = Some(now);
+
+        let before = self.workspace.current_buffer.as_mut().map(|buffer| {
+            if paused {
+                buffer.break_operation_group();
+            }
+            (buffer.revision(), buffer.cursor.position)
+        });
+
+        let result = self.dispatch_input(event);
+
+        if let (true, Some((revision, position)), Some(buffer)) = (
+            self.mode_key.is_editing(),
+            before,
+            self.workspace.current_buffer.as_mut(),
+        ) {
+            if buffer.revision() == revision && buffer.cursor.position != position {
+                buffer.break_operation_group();
+            }
+        }
+        result
+    }
+
     fn handle_input(&mut self, event: Event) -> Result<()> {
-        let key = InputMapper::event_map_str(event);
-        if key.is_none() {
+        // 宏与 `.` 回放时产生的按键不参与记录
+        let tracking = self.macros.depth == 0 && !self.repeat.replaying;
+        if let (true, Some(key)) = (tracking, InputMapper::event_record_str(&event)) {
+            self.repeat.push(
+                key,
+                self.mode_key == ModeKey::Normal,
+                self.mode_key.is_editing(),
+                self.cmd_counter,
+                self.buffer_revision(),
+            );
+        }
+
+        let result = if self.mode_key.is_editing() {
+            self.dispatch_edit_input(event)
+        } else {
+            self.dispatch_input(event)
+        };
+
+        if tracking {
+            self.finish_change();
+        }
+        result
+    }
+
+    // 回到Normal模式且没有等待中的按键时，一次修改结束
+    fn finish_change(&mut self) {
+        if self.mode_key == ModeKey::Normal
+            && self.macros.pending.is_none()
+            && self.pending_keys.is_empty()
+        {
+            let revision = self.buffer_revision();
+            self.repeat.finish(revision);
+        }
+    }
+
+    fn buffer_revision(&self) -> Option<(usize, usize)> {
+        self.workspace
+            .current_buffer
+            .as_ref()
+            .and_then(|buffer| Some((buffer.id?, buffer.revision())))
+    }
+
+    /// 在光标处回放最近一次修改，整个回放作为一个撤销单元
+    pub fn repeat_last_change(&mut self) -> Result<()> {
+        // 触发回放的按键本身不是一次修改
+        self.repeat.discard();
+        let change = match self.repeat.last() {
+            Some(change) => change.clone(),
+            None => return Ok(()),
+        };
+        let count = if self.cmd_counter > 0 {
+            self.cmd_counter
+        } else {
+            change.count
+        };
+
+        if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+            buffer.start_operation_group();
+        }
+        self.repeat.replaying = true;
+        self.cmd_counter = count;
+        let result = self.replay_keys(&change.keys_with_count(count), 1);
+        // 插入会话等不使用计数的修改不会清除计数
+        self.cmd_counter = 0;
+        self.repeat.replaying = false;
+        if let Some(buffer) = self.workspace.current_buffer.as_mut() {
+            buffer.end_operation_group();
+        }
+
+        self.repeat.set_last_count(count);
+        result
+    }
+
+    fn dispatch_input(&mut self, event: Event) -> Result<()> {
+        if let Some(key) = InputMapper::event_record_str(&event) {
+            self.macros.record(key);
+        }
+        if let Event::Key(key_event) = event {
+            // q/@ 之后的按键为寄存器名
+            if let Some(pending) = self.macros.pending.take() {
+                return self.resolve_macro_pending(pending, key_event);
+            }
+        }
+
+        let key = match (InputMapper::event_map_str(event.clone()), event) {
+            (Some(key), Event::Key(key_event)) => (key, key_event),
+            (Some(key), Event::Mouse(_) | Event::Paste(_)) => return self.dispatch_single_key(key),
+            _ => return Ok(()),
+        };
+
+        if self.accept_count(&key.1) {
             return Ok(());
         }
 
-        let key = key.unwrap();
-        if let Some(mode_key) = self.mode_key.to_string() {
-            if let Some(mapper) = self.input_map.get(&mode_key) {
-                if let Some(commands) = mapper.get(&key).cloned() {
-                    for command in commands {
-                        command(self)?;
-                    }
-                } else {
-                    if let Some(commands) = mapper.get("_").cloned() {
-                        for command in commands {
-                            command(self)?;
-                        }
-                    }
+        self.pending_keys.push(key);
+        self.resolve_pending_keys(false)
+    }
+
+    // 鼠标与粘贴事件只按单个按键匹配，等待中的按键序列按超时处理
+    fn dispatch_single_key(&mut self, key: String) -> Result<()> {
+        self.resolve_pending_keys(true)?;
+        let action = self
+            .mode_key
+            .to_string()
+            .and_then(|mode| self.keymaps.get(&mode))
+            .and_then(|keymap| keymap.get(&[key]))
+            .and_then(KeyTrie::action)
+            .cloned();
+        self.run_key_action(action)
+    }
+
+    fn run_key_action(&mut self, action: Option<KeyAction>) -> Result<()> {
+        match action {
+            Some(KeyAction::Handlers(_, commands)) => {
+                for command in commands {
+                    command(self)?;
                 }
             }
+            Some(KeyAction::Macro(register)) => {
+                let count = self.cmd_counter.max(1);
+                self.cmd_counter = 0;
+                self.play_macro(register, count)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// 计数前缀在匹配按键序列之前处理，计数为0时的 `0` 仍按绑定处理
+    fn accept_count(&mut self, key_event: &KeyEvent) -> bool {
+        let counting = self.pending_keys.is_empty()
+            && self
+                .mode_key
+                .to_string()
+                .is_some_and(|mode| self.count_modes.contains(&mode));
+        let digit = match key_event.code {
+            KeyCode::Char(c) if key_event.modifiers.is_empty() => c.to_digit(10),
+            _ => None,
+        };
+
+        match digit {
+            Some(digit) if counting && (digit != 0 || self.cmd_counter > 0) => {
+                self.push_count_digit(digit);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 在计数之后追加一位数字
+    pub fn push_count_digit(&mut self, digit: u32) {
+        self.cmd_counter = self.cmd_counter * 10 + digit as usize;
+        self.monitor.pending_keys = self.cmd_counter.to_string();
+    }
+
+    /// 按前缀树匹配已输入的按键，`timeout` 为true时不再等待后续按键
+    ///
+    /// 已输入的按键是某个更长序列的前缀时继续等待；否则执行已绑定的最长前缀，
+    /// 没有任何绑定时将第一个按键交给 `_`，剩余的按键重新匹配。
+    fn resolve_pending_keys(&mut self, timeout: bool) -> Result<()> {
+        self.pending_deadline = None;
+        let result = self.resolve_pending_keys_inner(timeout);
+        if result.is_err() {
+            self.pending_keys.clear();
+        }
+        // 等待后续按键时连同计数一起显示
+        self.monitor.pending_keys = match (self.pending_keys.is_empty(), self.cmd_counter) {
+            (true, _) => String::new(),
+            (false, 0) => self
+                .pending_keys
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .collect(),
+            (false, count) => self
+                .pending_keys
+                .iter()
+                .fold(count.to_string(), |keys, (key, _)| keys + key),
+        };
+        result
+    }
+
+    fn resolve_pending_keys_inner(&mut self, timeout: bool) -> Result<()> {
+        while !self.pending_keys.is_empty() {
+            // 执行过程中可能切换模式，每次都按当前模式匹配
+            let keymap = match self
+                .mode_key
+                .to_string()
+                .and_then(|mode| self.keymaps.get(&mode))
+            {
+                Some(keymap) => keymap,
+                None => {
+                    self.pending_keys.clear();
+                    break;
+                }
+            };
+
+            let keys: Vec<String> = self
+                .pending_keys
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect();
+            if !timeout && keymap.get(&keys).is_some_and(KeyTrie::is_prefix) {
+                self.pending_deadline = Some(Instant::now() + self.key_timeout);
+                break;
+            }
+
+            let (len, action) = match keymap.longest_match(&keys) {
+                Some((len, action)) => (len, Some(action.clone())),
+                None => (
+                    1,
+                    keymap
+                        .get(&[FALLBACK_KEY.to_string()])
+                        .and_then(KeyTrie::action)
+                        .cloned(),
+                ),
+            };
+
+            let (_, key_event) = self.pending_keys[len - 1];
+            self.pending_keys.drain(..len);
+            self.monitor.last_key = Some(key_event);
+            self.run_key_action(action)?;
+
+            if self.mode_key == ModeKey::Exit {
+                self.pending_keys.clear();
+            }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Application, ModeKey};
+    use std::{env, fs, path::Path, sync::Once};
+
+    static CONFIG: Once = Once::new();
+
+    // 配置与历史记录写到临时目录，不影响用户自己的配置
+    fn headless(dir: &Path, file: &str) -> Application {
+        CONFIG.call_once(|| {
+            let home = tempfile::tempdir().unwrap().keep();
+            env::set_var("XDG_CONFIG_HOME", home.join("config"));
+            env::set_var("XDG_DATA_HOME", home.join("data"));
+        });
+        let args = vec![
+            "held".to_string(),
+            dir.join(file).to_string_lossy().to_string(),
+        ];
+        Application::headless(&args).unwrap()
+    }
+
+    fn script(commands: &[&str]) -> Vec<String> {
+        commands.iter().map(|command| command.to_string()).collect()
+    }
+
+    #[test]
+    fn test_script_fails_with_modified_hidden_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "b\na\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "x\n").unwrap();
+
+        let mut app = headless(dir.path(), "a.txt");
+        let open = format!("e {}", dir.path().join("b.txt").display());
+        let status = app.run_script(&script(&["%!sort", &open])).unwrap();
+
+        // 修改过的a.txt已不是当前buffer，同样算作未保存
+        assert_eq!(status, 1);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "b\na\n"
+        );
+    }
+
+    #[test]
+    fn test_script_quit_fails_with_modified_buffers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "b\na\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "y\nx\n").unwrap();
+
+        let mut app = headless(dir.path(), "a.txt");
+        let open = format!("e {}", dir.path().join("b.txt").display());
+        let status = app
+            .run_script(&script(&["%!sort", &open, "%!sort", "q"]))
+            .unwrap();
+
+        // 不能进入等待回答的询问
+        assert_eq!(status, 1);
+        assert_ne!(app.mode_key, ModeKey::Prompt);
+        assert_ne!(app.mode_key, ModeKey::Exit);
+    }
+}
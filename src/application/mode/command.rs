@@ -2,8 +2,14 @@ use held_core::{
     utils::position::Position,
     view::{colors::Colors, style::CharStyle},
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::view::status_data::StatusLineData;
+use crate::modules::cmdline::{InputHistory, LineEditor};
+use crate::view::{
+    presenter::Presenter,
+    status_line::{StatusLine, INPUT_SEGMENT},
+};
 
 use super::{ModeData, ModeRenderer};
 
@@ -19,56 +25,111 @@ impl ModeRenderer for CommandRenderer {
         mode: &mut super::ModeData,
     ) -> super::Result<()> {
         let line = monitor.height()? - 1;
+        let width = monitor.width()?;
         let mut presenter = monitor.build_presenter()?;
 
-        if let Some(buffer) = &workspace.current_buffer {
+        if let (Some(buffer), ModeData::Command(command_data)) = (&workspace.current_buffer, mode) {
             let data = buffer.data();
             presenter.print_buffer(buffer, &data, &workspace.syntax_set, None, None)?;
 
-            let mode_name_data = StatusLineData {
-                content: " COMMAND ".to_string(),
-                color: Colors::Inverted,
-                style: CharStyle::Bold,
-            };
-
-            let cmd_str = if let ModeData::Command(command_data) = mode {
-                command_data.input.clone()
-            } else {
-                String::new()
-            };
-            let command_line_str = ":".to_owned() + &cmd_str;
-            let command_data = StatusLineData {
-                content: command_line_str.clone(),
-                color: Colors::Default,
-                style: CharStyle::Default,
-            };
-
-            presenter.print_status_line(&[mode_name_data, command_data])?;
-
-            let offset = " COMMAND ".len() + command_line_str.len();
-            presenter.set_cursor(Position { line, offset });
+            let placed = presenter.print_status_line(
+                StatusLine::new("COMMAND")
+                    .input(format!(":{}", command_data.input.text()))
+                    .buffer(&workspace.current_buffer),
+            )?;
+
+            // 光标位于命令输入中的编辑位置
+            if let Some(input) = placed
+                .iter()
+                .find(|segment| segment.name.as_deref() == Some(INPUT_SEGMENT))
+            {
+                let before = 1 + command_data.input.before_cursor().width();
+                let offset = input.offset + before.min(input.width);
+                presenter.set_cursor(Position { line, offset });
+            }
+
+            if let Some(completion) = &command_data.completion {
+                if line > 0 {
+                    print_wildmenu(&mut presenter, completion, line - 1, width);
+                }
+            }
 
             presenter.present()?;
-        } else {
         }
 
         Ok(())
     }
 }
 
+// 在状态栏之上的一行横向列出补全候选项，保持所选项可见
+fn print_wildmenu(presenter: &mut Presenter, completion: &Completion, line: usize, width: usize) {
+    let widths: Vec<usize> = completion
+        .candidates
+        .iter()
+        .map(|candidate| candidate.graphemes(true).count() + 2)
+        .collect();
+    let selected = completion.selected.unwrap_or(0);
+    let mut first = 0;
+    while first < selected && widths[first..=selected].iter().sum::<usize>() > width {
+        first += 1;
+    }
+
+    presenter.print_str(
+        &Position { line, offset: 0 },
+        CharStyle::Default,
+        Colors::Focused,
+        &" ".repeat(width),
+    );
+    let mut offset = 0;
+    for (index, candidate) in completion.candidates.iter().enumerate().skip(first) {
+        if offset + widths[index] > width {
+            break;
+        }
+        let colors = if completion.selected == Some(index) {
+            Colors::Inverted
+        } else {
+            Colors::Focused
+        };
+        presenter.print_str(
+            &Position { line, offset },
+            CharStyle::Default,
+            colors,
+            &format!(" {candidate} "),
+        );
+        offset += widths[index];
+    }
+}
+
+/// Tab补全的候选项
+#[derive(Debug)]
+pub struct Completion {
+    // 被补全的文本在输入中的起始位置
+    pub start: usize,
+    pub candidates: Vec<String>,
+    // 当前填入输入的候选项，None表示尚未选择
+    pub selected: Option<usize>,
+}
+
 #[derive(Debug)]
 pub struct CommandData {
-    pub input: String,
+    pub input: LineEditor,
+    pub history: InputHistory,
+    // 按Tab后出现的补全菜单，编辑输入后关闭
+    pub completion: Option<Completion>,
 }
 
 impl CommandData {
-    pub fn new() -> Self {
+    pub fn new(history: InputHistory) -> Self {
         CommandData {
-            input: String::new(),
+            input: LineEditor::default(),
+            history,
+            completion: None,
         }
     }
 
     pub fn reset(&mut self) {
         self.input.clear();
+        self.history.reset();
+        self.completion = None;
     }
 }
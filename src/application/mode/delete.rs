@@ -1,10 +1,5 @@
-use held_core::view::{colors::Colors, style::CharStyle};
-
 use super::ModeRenderer;
-use crate::{
-    errors::*,
-    view::status_data::{buffer_status_data, StatusLineData},
-};
+use crate::{errors::*, view::status_line::StatusLine};
 pub(super) struct DeleteRenderer;
 
 impl ModeRenderer for DeleteRenderer {
@@ -19,15 +14,8 @@ impl ModeRenderer for DeleteRenderer {
             let data = buffer.data();
             presenter.print_buffer(buffer, &data, &workspace.syntax_set, None, None)?;
 
-            let mode_name_data = StatusLineData {
-                content: " DELETE ".to_string(),
-                color: Colors::Inverted,
-                style: CharStyle::Bold,
-            };
-            presenter.print_status_line(&[
-                mode_name_data,
-                buffer_status_data(&workspace.current_buffer),
-            ])?;
+            presenter
+                .print_status_line(StatusLine::new("DELETE").buffer(&workspace.current_buffer))?;
 
             presenter.present()?;
         } else {
@@ -1,16 +1,22 @@
 use super::ModeRenderer;
-use crate::{application::mode::ModeData, errors::*};
+use crate::{
+    application::mode::ModeData, errors::*, modules::messages::Message,
+    view::status_line::StatusLine,
+};
 pub struct ErrorRenderer;
 
+/// 当前模式与上一个模式都无法渲染时，只在状态栏上显示错误
 impl ModeRenderer for ErrorRenderer {
     fn render(
         _workspace: &mut crate::workspace::Workspace,
-        _monitor: &mut crate::view::monitor::Monitor,
+        monitor: &mut crate::view::monitor::Monitor,
         mode: &mut super::ModeData,
     ) -> Result<()> {
         if let ModeData::Error(e) = mode {
-            panic!("{e:?}");
+            monitor.message = Some(Message::from_error(e));
         }
-        todo!()
+        let mut presenter = monitor.build_presenter()?;
+        presenter.print_status_line(StatusLine::new("ERROR"))?;
+        presenter.present()
     }
 }
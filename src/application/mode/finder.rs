@@ -0,0 +1,255 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crossterm::style::Color;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use held_core::{
+    utils::position::Position,
+    view::{colors::Colors, style::CharStyle},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::{ModeData, ModeRenderer};
+use crate::{
+    errors::*,
+    modules::{cmdline::LineEditor, file_filter::FileFilter},
+    view::{
+        presenter::Presenter,
+        status_line::{StatusLine, INPUT_SEGMENT},
+    },
+};
+
+// 预览时读取的最大字节数
+const PREVIEW_BYTES: usize = 64 * 1024;
+// 屏幕宽度不小于该值时在右半边显示预览
+const PREVIEW_MIN_WIDTH: usize = 60;
+
+/// 模糊查找工作区中的文件
+#[derive(Default)]
+pub struct FinderData {
+    pub input: LineEditor,
+    root: PathBuf,
+    // 工作区中未被忽略的文件，相对于工作区的路径
+    files: Vec<String>,
+    // 按得分排列的匹配结果：文件的序号与匹配到的字符位置
+    matches: Vec<(usize, Vec<usize>)>,
+    // 计算matches时的输入，输入改变后重新匹配
+    matched_query: Option<String>,
+    selected: usize,
+    // 预览的文件序号与内容
+    preview: Option<(usize, Vec<String>)>,
+}
+
+impl FinderData {
+    /// 重新列出root下的文件并清空输入
+    pub fn load(&mut self, root: &Path, ignore_patterns: &[String]) {
+        *self = FinderData {
+            root: root.to_path_buf(),
+            files: FileFilter::new(root, ignore_patterns)
+                .walk()
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect(),
+            ..Default::default()
+        };
+    }
+
+    /// 按当前输入重新匹配，得分相同时较短的路径在前
+    pub fn update_matches(&mut self) {
+        let query = self.input.text();
+        if self.matched_query.as_deref() == Some(query) {
+            return;
+        }
+
+        self.matches = if query.is_empty() {
+            (0..self.files.len())
+                .map(|index| (index, Vec::new()))
+                .collect()
+        } else {
+            let matcher = SkimMatcherV2::default().smart_case();
+            let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+                .files
+                .iter()
+                .enumerate()
+                .filter_map(|(index, file)| {
+                    let (score, indices) = matcher.fuzzy_indices(file, query)?;
+                    Some((score, index, indices))
+                })
+                .collect();
+            scored.sort_by(|(a_score, a, _), (b_score, b, _)| {
+                b_score
+                    .cmp(a_score)
+                    .then(self.files[*a].len().cmp(&self.files[*b].len()))
+                    .then(a.cmp(b))
+            });
+            scored
+                .into_iter()
+                .map(|(_, index, indices)| (index, indices))
+                .collect()
+        };
+        self.matched_query = Some(query.to_string());
+        self.selected = 0;
+    }
+
+    pub fn move_selection(&mut self, forward: bool) {
+        self.selected = match forward {
+            true => (self.selected + 1).min(self.matches.len().saturating_sub(1)),
+            false => self.selected.saturating_sub(1),
+        };
+    }
+
+    /// 当前选择的文件
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let (index, _) = self.matches.get(self.selected)?;
+        Some(self.root.join(&self.files[*index]))
+    }
+
+    // 读取所选文件的开头用于预览，二进制文件不预览
+    fn preview_lines(&mut self) -> &[String] {
+        let index = match self.matches.get(self.selected) {
+            Some((index, _)) => *index,
+            None => return &[],
+        };
+        if self.preview.as_ref().map(|(file, _)| *file) != Some(index) {
+            let mut content = Vec::new();
+            let lines = match File::open(self.root.join(&self.files[index])) {
+                Ok(file) => match file.take(PREVIEW_BYTES as u64).read_to_end(&mut content) {
+                    Ok(_) if content.contains(&0) => vec!["[binary file]".to_string()],
+                    Ok(_) => String::from_utf8_lossy(&content)
+                        .lines()
+                        .map(|line| line.replace('\t', "    "))
+                        .collect(),
+                    Err(e) => vec![e.to_string()],
+                },
+                Err(e) => vec![e.to_string()],
+            };
+            self.preview = Some((index, lines));
+        }
+        self.preview.as_ref().map_or(&[], |(_, lines)| &lines[..])
+    }
+}
+
+pub(super) struct FinderRenderer;
+
+impl ModeRenderer for FinderRenderer {
+    fn render(
+        workspace: &mut crate::workspace::Workspace,
+        monitor: &mut crate::view::monitor::Monitor,
+        mode: &mut super::ModeData,
+    ) -> Result<()> {
+        let line = monitor.height()? - 1;
+        let width = monitor.width()?;
+        let mut presenter = monitor.build_presenter()?;
+
+        if let ModeData::Finder(finder_data) = mode {
+            finder_data.update_matches();
+
+            let list_width = if width >= PREVIEW_MIN_WIDTH {
+                width / 2
+            } else {
+                width
+            };
+            print_matches(&mut presenter, finder_data, line, list_width);
+            if list_width < width {
+                let offset = list_width + 1;
+                for row in 0..line {
+                    presenter.print_str(
+                        &Position {
+                            line: row,
+                            offset: list_width,
+                        },
+                        CharStyle::Default,
+                        Colors::Focused,
+                        "│",
+                    );
+                }
+                for (row, content) in finder_data.preview_lines().iter().take(line).enumerate() {
+                    presenter.print_str(
+                        &Position { line: row, offset },
+                        CharStyle::Default,
+                        Colors::Default,
+                        content,
+                    );
+                }
+            }
+
+            let placed = presenter.print_status_line(
+                StatusLine::new("FINDER")
+                    .input(format!(
+                        "> {}  {}/{}",
+                        finder_data.input.text(),
+                        finder_data.matches.len(),
+                        finder_data.files.len()
+                    ))
+                    .buffer(&workspace.current_buffer),
+            )?;
+            if let Some(input) = placed
+                .iter()
+                .find(|segment| segment.name.as_deref() == Some(INPUT_SEGMENT))
+            {
+                let before = 2 + finder_data.input.before_cursor().width();
+                let offset = input.offset + before.min(input.width);
+                presenter.set_cursor(Position { line, offset });
+            }
+
+            presenter.present()?;
+        }
+
+        Ok(())
+    }
+}
+
+// 从第一行开始列出匹配的文件，匹配到的字符加粗，保持所选项可见
+fn print_matches(presenter: &mut Presenter, finder_data: &FinderData, rows: usize, width: usize) {
+    let first = (finder_data.selected + 1).saturating_sub(rows);
+    for (row, (index, (file, indices))) in finder_data
+        .matches
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .enumerate()
+    {
+        let selected = index == finder_data.selected;
+        let colors = if selected {
+            Colors::Inverted
+        } else {
+            Colors::Default
+        };
+        let mut offset = 0;
+        for (char_index, c) in finder_data.files[*file].chars().enumerate() {
+            if offset >= width {
+                break;
+            }
+            let (style, colors) = if indices.contains(&char_index) && !selected {
+                (CharStyle::Bold, Colors::CustomForeground(Color::Yellow))
+            } else if indices.contains(&char_index) {
+                (CharStyle::Bold, colors)
+            } else {
+                (CharStyle::Default, colors)
+            };
+            offset += presenter.print_str(
+                &Position { line: row, offset },
+                style,
+                colors,
+                c.encode_utf8(&mut [0; 4]),
+            );
+        }
+        if selected {
+            presenter.print_str(
+                &Position { line: row, offset },
+                CharStyle::Default,
+                colors,
+                &" ".repeat(width.saturating_sub(offset)),
+            );
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use held_core::view::{colors::Colors, style::CharStyle};
-
-use crate::view::status_data::{buffer_status_data, StatusLineData};
+use crate::view::status_line::StatusLine;
 
 use super::ModeRenderer;
 
@@ -18,15 +16,8 @@ impl ModeRenderer for InsertRenderer {
             let data = buffer.data();
             presenter.print_buffer(buffer, &data, &workspace.syntax_set, None, None)?;
 
-            let mode_name_data = StatusLineData {
-                content: " INSERT ".to_string(),
-                color: Colors::Inverted,
-                style: CharStyle::Bold,
-            };
-            presenter.print_status_line(&[
-                mode_name_data,
-                buffer_status_data(&workspace.current_buffer),
-            ])?;
+            presenter
+                .print_status_line(StatusLine::new("INSERT").buffer(&workspace.current_buffer))?;
 
             presenter.present()?;
         } else {
@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use held_core::{
+    utils::position::Position,
+    view::{colors::Colors, style::CharStyle},
+};
+
+use super::{ModeData, ModeRenderer};
+use crate::{
+    errors::*,
+    modules::location::Location,
+    view::{presenter::Presenter, status_line::StatusLine},
+};
+
+// 屏幕高度不小于该值时在列表下方显示预览
+const PREVIEW_MIN_HEIGHT: usize = 12;
+
+/// 可以逐项浏览并跳转的位置列表，如快速修复列表
+#[derive(Default)]
+pub struct ListData {
+    title: String,
+    root: PathBuf,
+    items: Vec<Location>,
+    selected: usize,
+    // 预览的文件与其内容
+    preview: Option<(PathBuf, Vec<String>)>,
+}
+
+impl ListData {
+    /// 显示新的列表并选中第selected项，root下的文件显示相对路径
+    pub fn load(&mut self, title: String, root: &Path, items: Vec<Location>, selected: usize) {
+        *self = ListData {
+            title,
+            root: root.to_path_buf(),
+            items,
+            selected,
+            ..Default::default()
+        };
+    }
+
+    pub fn move_selection(&mut self, forward: bool) {
+        self.selected = match forward {
+            true => (self.selected + 1).min(self.items.len().saturating_sub(1)),
+            false => self.selected.saturating_sub(1),
+        };
+    }
+
+    pub fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    pub fn select_last(&mut self) {
+        self.selected = self.items.len().saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<&Location> {
+        self.items.get(self.selected)
+    }
+
+    // 所选位置所在文件的内容，读取失败时显示错误
+    fn preview_lines(&mut self) -> &[String] {
+        let path = match self.items.get(self.selected) {
+            Some(item) => &item.path,
+            None => return &[],
+        };
+        if self.preview.as_ref().map(|(file, _)| file) != Some(path) {
+            let lines = match fs::read(path) {
+                Ok(content) => String::from_utf8_lossy(&content)
+                    .lines()
+                    .map(|line| line.replace('\t', "    "))
+                    .collect(),
+                Err(e) => vec![e.to_string()],
+            };
+            self.preview = Some((path.clone(), lines));
+        }
+        self.preview.as_ref().map_or(&[], |(_, lines)| &lines[..])
+    }
+}
+
+pub(super) struct ListRenderer;
+
+impl ModeRenderer for ListRenderer {
+    fn render(
+        workspace: &mut crate::workspace::Workspace,
+        monitor: &mut crate::view::monitor::Monitor,
+        mode: &mut super::ModeData,
+    ) -> Result<()> {
+        let rows = monitor.height()? - 1;
+        let width = monitor.width()?;
+        let mut presenter = monitor.build_presenter()?;
+
+        if let ModeData::List(list_data) = mode {
+            let list_rows = if rows >= PREVIEW_MIN_HEIGHT {
+                rows / 2
+            } else {
+                rows
+            };
+            print_items(&mut presenter, list_data, list_rows, width);
+            if list_rows < rows {
+                print_preview(&mut presenter, list_data, list_rows, rows, width);
+            }
+
+            presenter.print_status_line(
+                StatusLine::new("LIST")
+                    .input(format!(
+                        "{}  {}/{}",
+                        list_data.title,
+                        (list_data.selected + 1).min(list_data.items.len()),
+                        list_data.items.len()
+                    ))
+                    .buffer(&workspace.current_buffer),
+            )?;
+
+            presenter.present()?;
+        }
+
+        Ok(())
+    }
+}
+
+// 从第一行开始列出各项，保持所选项可见
+fn print_items(presenter: &mut Presenter, list_data: &ListData, rows: usize, width: usize) {
+    let first = (list_data.selected + 1).saturating_sub(rows);
+    for (row, (index, item)) in list_data
+        .items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .enumerate()
+    {
+        let colors = if index == list_data.selected {
+            Colors::Inverted
+        } else {
+            Colors::Default
+        };
+        let description = item.describe(&list_data.root).replace('\t', "    ");
+        let offset = presenter.print_str(
+            &Position {
+                line: row,
+                offset: 0,
+            },
+            CharStyle::Default,
+            colors,
+            &description,
+        );
+        if index == list_data.selected {
+            presenter.print_str(
+                &Position { line: row, offset },
+                CharStyle::Default,
+                colors,
+                &" ".repeat(width.saturating_sub(offset)),
+            );
+        }
+    }
+}
+
+// 在top行画出分隔线，其下显示所选行附近的内容，所选行居中并高亮
+fn print_preview(
+    presenter: &mut Presenter,
+    list_data: &mut ListData,
+    top: usize,
+    rows: usize,
+    width: usize,
+) {
+    let (name, line) = match list_data.selected_item() {
+        Some(item) => (
+            item.path
+                .strip_prefix(&list_data.root)
+                .unwrap_or(&item.path)
+                .to_string_lossy()
+                .to_string(),
+            item.line,
+        ),
+        None => (String::new(), 0),
+    };
+    let title = format!("── {name} ");
+    let offset = presenter.print_str(
+        &Position {
+            line: top,
+            offset: 0,
+        },
+        CharStyle::Default,
+        Colors::Focused,
+        &title,
+    );
+    presenter.print_str(
+        &Position { line: top, offset },
+        CharStyle::Default,
+        Colors::Focused,
+        &"─".repeat(width.saturating_sub(offset)),
+    );
+
+    let height = rows - top - 1;
+    let first = line.saturating_sub(height / 2);
+    let lines = list_data.preview_lines();
+    for (row, (number, content)) in lines
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(height)
+        .enumerate()
+    {
+        let colors = if number == line {
+            Colors::Focused
+        } else {
+            Colors::Default
+        };
+        let text = format!("{:>5} {content}", number + 1);
+        let position = Position {
+            line: top + 1 + row,
+            offset: 0,
+        };
+        let offset = presenter.print_str(&position, CharStyle::Default, colors, &text);
+        if number == line {
+            presenter.print_str(
+                &Position {
+                    line: position.line,
+                    offset,
+                },
+                CharStyle::Default,
+                colors,
+                &" ".repeat(width.saturating_sub(offset)),
+            );
+        }
+    }
+}
@@ -1,19 +1,23 @@
 use std::collections::HashMap;
 
 use crate::errors::*;
+use crate::modules::cmdline::LineEditor;
+use crate::modules::input::keymap::{check_keys, parse_sequence, KeyAction, KeyTrie};
 use crate::{view::monitor::Monitor, workspace::Workspace};
 use command::{CommandData, CommandRenderer};
 use delete::DeleteRenderer;
 use error::ErrorRenderer;
-use error_chain::bail;
+use finder::{FinderData, FinderRenderer};
 use insert::InsertRenderer;
 use linked_hash_map::LinkedHashMap;
+use list::{ListData, ListRenderer};
 use normal::NormalRenderer;
+use prompt::{PromptData, PromptRenderer};
 use replace::ReplaceRenderer;
 use search::{SearchData, SearchRenderer};
-use smallvec::SmallVec;
-use strum::EnumIter;
-use workspace::{WorkspaceModeData, WorkspaceRender};
+use select::{SelectData, SelectRenderer};
+use strum::{EnumIter, IntoEnumIterator};
+use workspace::WorkspaceRender;
 use yaml_rust::Yaml;
 
 use super::handler::handle_map;
@@ -22,23 +26,35 @@ use super::Application;
 pub mod command;
 pub mod delete;
 pub mod error;
+pub mod finder;
 mod insert;
+pub mod list;
 pub mod motion;
 pub mod normal;
+pub mod prompt;
 mod replace;
 pub mod search;
+pub mod select;
 pub mod workspace;
 
+// 旧配置中代表数字键的按键名
+const DEPRECATED_NUM_KEY: &str = "num";
+
 pub enum ModeData {
     Normal,
     Error(Error),
     Exit,
     Insert,
     Command(CommandData),
-    Workspace(WorkspaceModeData),
+    Workspace,
     Search(SearchData),
     Delete,
-    Replace, // Other(OtherData)
+    Replace,
+    Select(SelectData),
+    Prompt(PromptData),
+    Finder(FinderData),
+    List(ListData),
+    TreeFilter(LineEditor), // Other(OtherData)
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumIter)]
@@ -52,9 +68,19 @@ pub enum ModeKey {
     Search,
     Delete,
     Replace,
+    Select,
+    Prompt,
+    Finder,
+    List,
+    TreeFilter,
 }
 
 impl ModeKey {
+    /// 插入与替换模式直接修改buffer内容
+    pub fn is_editing(&self) -> bool {
+        matches!(self, ModeKey::Insert | ModeKey::Replace)
+    }
+
     pub fn to_string(&self) -> Option<String> {
         match self {
             ModeKey::Normal => Some("normal".into()),
@@ -64,86 +90,112 @@ impl ModeKey {
             ModeKey::Search => Some("search".into()),
             ModeKey::Delete => Some("delete".into()),
             ModeKey::Replace => Some("replace".into()),
+            ModeKey::Select => Some("select".into()),
+            ModeKey::Prompt => Some("prompt".into()),
+            ModeKey::Finder => Some("finder".into()),
+            ModeKey::List => Some("list".into()),
+            ModeKey::TreeFilter => Some("tree_filter".into()),
             _ => None,
         }
     }
 
+    /// 由配置文件中的模式名得到模式，如 `tree_filter`
+    pub fn from_name(name: &str) -> Option<ModeKey> {
+        ModeKey::iter().find(|mode| mode.to_string().as_deref() == Some(name))
+    }
+
+    /// 解析该模式的按键绑定，错误的绑定被跳过并记录在errors中
     pub fn generate_handle_map(
         &self,
-        mode_map: &mut HashMap<
-            String,
-            HashMap<String, SmallVec<[fn(&mut Application) -> Result<()>; 4]>>,
-        >,
+        keymaps: &mut HashMap<String, KeyTrie>,
         extra: Option<&LinkedHashMap<Yaml, Yaml>>,
         default: &LinkedHashMap<Yaml, Yaml>,
-    ) -> Result<()> {
+        leader: &str,
+        errors: &mut Vec<String>,
+    ) {
         let handle_map = handle_map();
-        let mut command_map =
-            HashMap::<String, SmallVec<[fn(&mut Application) -> Result<()>; 4]>>::new();
+        let mut keymap = KeyTrie::default();
         if let Some(mode) = self.to_string() {
-            if let Some(yaml) = default.get(&Yaml::String(mode.clone())) {
-                if let Some(keys) = yaml.as_hash() {
-                    self.parse_mode_keybindings(keys, &handle_map, &mut command_map)?;
-                }
-            }
-
-            if let Some(extra) = extra {
-                if let Some(yaml) = extra.get(&Yaml::String(mode.clone())) {
-                    if let Some(keys) = yaml.as_hash() {
-                        self.parse_mode_keybindings(keys, &handle_map, &mut command_map)?;
+            for config in Some(default).into_iter().chain(extra) {
+                match config.get(&Yaml::String(mode.clone())) {
+                    Some(Yaml::Hash(keys)) => {
+                        Self::parse_mode_keybindings(
+                            &mode,
+                            keys,
+                            &handle_map,
+                            &mut keymap,
+                            leader,
+                            errors,
+                        );
                     }
+                    Some(yaml) => {
+                        errors.push(format!("{mode}: {yaml:?} isn't a hash of key bindings"))
+                    }
+                    None => {}
                 }
             }
-            mode_map.insert(mode, command_map);
+            keymaps.insert(mode, keymap);
         }
-
-        Ok(())
     }
 
     fn parse_mode_keybindings(
-        &self,
+        mode: &str,
         keybindings: &LinkedHashMap<Yaml, Yaml>,
         handle_map: &HashMap<&str, fn(&mut Application) -> Result<()>>,
-        result: &mut HashMap<String, SmallVec<[fn(&mut Application) -> Result<()>; 4]>>,
-    ) -> Result<()> {
+        result: &mut KeyTrie,
+        leader: &str,
+        errors: &mut Vec<String>,
+    ) {
         for (key, handle) in keybindings {
-            if let Some(key) = key.as_str() {
-                let mut closures = SmallVec::new();
-
-                match handle {
-                    Yaml::String(command_key) => {
-                        closures.push(
-                            *handle_map
-                                .get(command_key.as_str())
-                                .ok_or_else(|| format!("command \"{command_key:?}\" not found"))?,
-                        );
-                    }
-                    Yaml::Array(commands) => {
-                        for command in commands {
-                            let command_key = command.as_str().ok_or_else(|| {
-                                format!(
-                                    "Keymap command \"{:?}\" couldn't be parsed as a string",
-                                    command
-                                )
-                            })?;
-
-                            closures.push(
-                                *handle_map.get(command_key).ok_or_else(|| {
-                                    format!("command \"{command_key:?}\" not found")
-                                })?,
-                            );
-                        }
-                    }
-                    _ => {
-                        bail!(format!("conmand: \"{handle:?}\" couldn't be parsed"));
-                    }
+            let key = match key {
+                Yaml::String(key) => key.clone(),
+                // 未加引号的数字键
+                Yaml::Integer(digit) => digit.to_string(),
+                key => {
+                    errors.push(format!("{mode}: {key:?} isn't a key sequence"));
+                    continue;
                 }
+            };
+
+            // 已弃用的 `num` 代表任意一个数字键，0不能作为计数的开头，不绑定
+            if key == DEPRECATED_NUM_KEY {
+                errors.push(format!(
+                    "{mode}: \"{DEPRECATED_NUM_KEY}\" is deprecated, bind the digits 1-9 instead"
+                ));
+                let digits = (1..=9).map(|digit| (Yaml::String(digit.to_string()), handle.clone()));
+                Self::parse_mode_keybindings(
+                    mode,
+                    &digits.collect(),
+                    handle_map,
+                    result,
+                    leader,
+                    errors,
+                );
+                continue;
+            }
 
-                result.insert(key.to_string(), closures);
+            let keys = parse_sequence(&key, leader);
+            let action = check_keys(&keys).and_then(|_| {
+                let commands = match handle {
+                    Yaml::String(command) => vec![command.as_str()],
+                    Yaml::Array(commands) => commands
+                        .iter()
+                        .map(|command| {
+                            command.as_str().ok_or_else(|| {
+                                format!("command {command:?} couldn't be parsed as a string")
+                            })
+                        })
+                        .collect::<std::result::Result<Vec<_>, _>>()?,
+                    _ => return Err(format!("command {handle:?} couldn't be parsed").into()),
+                };
+                KeyAction::parse(&commands, handle_map)
+            });
+
+            match action {
+                Ok(action) => result.insert(&keys, action),
+                Err(e) => errors.push(format!("{mode}: \"{key}\": {e}")),
             }
         }
-
-        Ok(())
     }
 }
 
@@ -160,11 +212,17 @@ impl ModeRenderer for ModeRouter {
             ModeData::Error(_) => ErrorRenderer::render(workspace, monitor, mode),
             ModeData::Insert => InsertRenderer::render(workspace, monitor, mode),
             ModeData::Command(_) => CommandRenderer::render(workspace, monitor, mode),
-            ModeData::Workspace(_) => WorkspaceRender::render(workspace, monitor, mode),
+            ModeData::Workspace | ModeData::TreeFilter(_) => {
+                WorkspaceRender::render(workspace, monitor, mode)
+            }
             ModeData::Search(_) => SearchRenderer::render(workspace, monitor, mode),
             ModeData::Replace => ReplaceRenderer::render(workspace, monitor, mode),
             ModeData::Exit => todo!(),
             ModeData::Delete => DeleteRenderer::render(workspace, monitor, mode),
+            ModeData::Select(_) => SelectRenderer::render(workspace, monitor, mode),
+            ModeData::Prompt(_) => PromptRenderer::render(workspace, monitor, mode),
+            ModeData::Finder(_) => FinderRenderer::render(workspace, monitor, mode),
+            ModeData::List(_) => ListRenderer::render(workspace, monitor, mode),
         }
     }
 }
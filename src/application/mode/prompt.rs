@@ -0,0 +1,229 @@
+use held_core::{
+    utils::position::Position,
+    view::{colors::Colors, style::CharStyle},
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{ModeData, ModeKey, ModeRenderer};
+use crate::{
+    application::Application,
+    errors::*,
+    view::status_line::{StatusLine, INPUT_SEGMENT},
+};
+
+/// 用户对提示的回答
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptAnswer {
+    // 确认提示中所选选项的首字母
+    Key(char),
+    // 输入的文本
+    Text(String),
+    // 列表中所选项的序号
+    Choice(usize),
+    Cancel,
+}
+
+pub type PromptCallback = Box<dyn FnOnce(&mut Application, PromptAnswer) -> Result<()>>;
+
+pub enum PromptKind {
+    /// 按选项的首字母回答，如 `(y)es (n)o (c)ancel`
+    Confirm(Vec<String>),
+    /// 输入一行文本，相同history名的提示共享输入历史
+    Input {
+        input: String,
+        history: &'static str,
+        // 正在查看的历史记录，None表示正在编辑新的输入
+        history_index: Option<usize>,
+    },
+    /// 在列表中选择一项
+    Choice { items: Vec<String>, selected: usize },
+}
+
+/// 提示模式的数据
+///
+/// 回答后回到打开提示之前的模式，再以回答调用callback。
+pub struct PromptData {
+    pub message: String,
+    pub kind: PromptKind,
+    pub(in crate::application) callback: Option<PromptCallback>,
+    pub(in crate::application) return_mode: ModeKey,
+}
+
+impl PromptData {
+    pub fn confirm<F>(message: String, options: &[&str], callback: F) -> PromptData
+    where
+        F: FnOnce(&mut Application, PromptAnswer) -> Result<()> + 'static,
+    {
+        Self::new(
+            message,
+            PromptKind::Confirm(options.iter().map(|option| option.to_string()).collect()),
+            callback,
+        )
+    }
+
+    pub fn input<F>(message: String, history: &'static str, callback: F) -> PromptData
+    where
+        F: FnOnce(&mut Application, PromptAnswer) -> Result<()> + 'static,
+    {
+        Self::new(
+            message,
+            PromptKind::Input {
+                input: String::new(),
+                history,
+                history_index: None,
+            },
+            callback,
+        )
+    }
+
+    /// 输入提示的初始文本
+    pub fn with_input<T: Into<String>>(mut self, text: T) -> PromptData {
+        if let PromptKind::Input { ref mut input, .. } = self.kind {
+            *input = text.into();
+        }
+        self
+    }
+
+    pub fn choice<F>(message: String, items: Vec<String>, callback: F) -> PromptData
+    where
+        F: FnOnce(&mut Application, PromptAnswer) -> Result<()> + 'static,
+    {
+        Self::new(message, PromptKind::Choice { items, selected: 0 }, callback)
+    }
+
+    fn new<F>(message: String, kind: PromptKind, callback: F) -> PromptData
+    where
+        F: FnOnce(&mut Application, PromptAnswer) -> Result<()> + 'static,
+    {
+        PromptData {
+            message,
+            kind,
+            callback: Some(Box::new(callback)),
+            return_mode: ModeKey::Normal,
+        }
+    }
+
+    /// 确认提示中首字母为key的选项
+    pub fn option_key(&self, key: char) -> Option<char> {
+        match &self.kind {
+            PromptKind::Confirm(options) => options
+                .iter()
+                .filter_map(|option| option.chars().next())
+                .find(|first| first.eq_ignore_ascii_case(&key))
+                .map(|first| first.to_ascii_lowercase()),
+            _ => None,
+        }
+    }
+
+    // 状态栏上显示的问题与输入
+    fn status_input(&self) -> String {
+        match &self.kind {
+            PromptKind::Confirm(options) => {
+                let options: Vec<String> = options
+                    .iter()
+                    .map(|option| {
+                        let mut chars = option.chars();
+                        match chars.next() {
+                            Some(first) => format!("({first}){}", chars.as_str()),
+                            None => String::new(),
+                        }
+                    })
+                    .collect();
+                format!("{} {}", self.message, options.join(" "))
+            }
+            PromptKind::Input { input, .. } => format!("{} {}", self.message, input),
+            PromptKind::Choice { .. } => self.message.clone(),
+        }
+    }
+}
+
+impl Default for PromptData {
+    fn default() -> Self {
+        PromptData {
+            message: String::new(),
+            kind: PromptKind::Confirm(Vec::new()),
+            callback: None,
+            return_mode: ModeKey::Normal,
+        }
+    }
+}
+
+pub(super) struct PromptRenderer;
+
+impl ModeRenderer for PromptRenderer {
+    fn render(
+        workspace: &mut crate::workspace::Workspace,
+        monitor: &mut crate::view::monitor::Monitor,
+        mode: &mut super::ModeData,
+    ) -> Result<()> {
+        let line = monitor.height()? - 1;
+        let width = monitor.width()?;
+        // 提示优先于消息显示
+        monitor.message = None;
+        let mut presenter = monitor.build_presenter()?;
+
+        let prompt_data = match mode {
+            ModeData::Prompt(prompt_data) => prompt_data,
+            _ => return Ok(()),
+        };
+
+        // 没有打开的buffer时（如在目录树中新建文件）仍然显示提示
+        let data = workspace
+            .current_buffer
+            .as_ref()
+            .map(|buffer| buffer.data());
+        if let (Some(buffer), Some(data)) = (&workspace.current_buffer, &data) {
+            presenter.print_buffer(buffer, data, &workspace.syntax_set, None, None)?;
+        }
+
+        let placed = presenter.print_status_line(
+            StatusLine::new("PROMPT")
+                .input(prompt_data.status_input())
+                .buffer(&workspace.current_buffer),
+        )?;
+
+        match &prompt_data.kind {
+            PromptKind::Input { .. } => {
+                if let Some(input) = placed
+                    .iter()
+                    .find(|segment| segment.name.as_deref() == Some(INPUT_SEGMENT))
+                {
+                    let offset = input.offset + input.width;
+                    presenter.set_cursor(Position { line, offset });
+                }
+            }
+            PromptKind::Choice { items, selected } => {
+                // 列表显示在状态栏之上，保持所选项可见
+                let rows = items.len().min(line);
+                let first = (selected + 1).saturating_sub(rows);
+                for (row, (index, item)) in
+                    items.iter().enumerate().skip(first).take(rows).enumerate()
+                {
+                    let colors = if index == *selected {
+                        Colors::Inverted
+                    } else {
+                        Colors::Focused
+                    };
+                    let mut content = format!(" {item}");
+                    let padding = width.saturating_sub(content.graphemes(true).count());
+                    content.push_str(&" ".repeat(padding));
+                    presenter.print_str(
+                        &Position {
+                            line: line - rows + row,
+                            offset: 0,
+                        },
+                        CharStyle::Default,
+                        colors,
+                        &content,
+                    );
+                }
+            }
+            PromptKind::Confirm(_) => {}
+        }
+
+        presenter.present()?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,4 @@
-use crate::view::status_data::{buffer_status_data, StatusLineData};
-use held_core::view::colors::Colors;
-use held_core::view::style::CharStyle;
+use crate::view::status_line::StatusLine;
 
 use super::ModeRenderer;
 
@@ -18,15 +16,8 @@ impl ModeRenderer for ReplaceRenderer {
             let data = buffer.data();
             presenter.print_buffer(buffer, &data, &workspace.syntax_set, None, None)?;
 
-            let mode_name_data = StatusLineData {
-                content: " REPLACE ".to_string(),
-                color: Colors::Inverted,
-                style: CharStyle::Bold,
-            };
-            presenter.print_status_line(&[
-                mode_name_data,
-                buffer_status_data(&workspace.current_buffer),
-            ])?;
+            presenter
+                .print_status_line(StatusLine::new("REPLACE").buffer(&workspace.current_buffer))?;
 
             presenter.present()?;
         } else {
@@ -1,12 +1,14 @@
 use super::ModeRenderer;
 use crate::{
     errors::*,
-    view::status_data::{buffer_status_data, StatusLineData},
+    modules::cmdline::{InputHistory, LineEditor},
+    view::status_line::{StatusLine, INPUT_SEGMENT},
 };
 use held_core::{
-    utils::range::Range,
+    utils::{position::Position, range::Range},
     view::{colors::Colors, style::CharStyle},
 };
+use unicode_width::UnicodeWidthStr;
 pub(super) struct SearchRenderer;
 
 impl ModeRenderer for SearchRenderer {
@@ -15,6 +17,7 @@ impl ModeRenderer for SearchRenderer {
         monitor: &mut crate::view::monitor::Monitor,
         _mode: &mut super::ModeData,
     ) -> Result<()> {
+        let line = monitor.height()? - 1;
         let mut presenter = monitor.build_presenter()?;
 
         if let Some(buffer) = &workspace.current_buffer {
@@ -48,23 +51,23 @@ impl ModeRenderer for SearchRenderer {
                     None,
                 )?;
 
-                let mode_name_data = StatusLineData {
-                    content: " Search/".to_string(),
-                    color: Colors::Inverted,
-                    style: CharStyle::Bold,
-                };
-
-                let search_data = StatusLineData {
-                    content: search_data.search_string.clone(),
-                    color: Colors::Default,
-                    style: CharStyle::Default,
-                };
+                let placed = presenter.print_status_line(
+                    StatusLine::new("SEARCH")
+                        .input(format!("/{}", search_data.input.text()))
+                        .buffer(&workspace.current_buffer),
+                )?;
 
-                presenter.print_status_line(&[
-                    mode_name_data,
-                    search_data,
-                    buffer_status_data(&workspace.current_buffer),
-                ])?;
+                // 输入搜索内容时光标位于编辑位置
+                if !search_data.is_exec_search {
+                    if let Some(input) = placed
+                        .iter()
+                        .find(|segment| segment.name.as_deref() == Some(INPUT_SEGMENT))
+                    {
+                        let before = 1 + search_data.input.before_cursor().width();
+                        let offset = input.offset + before.min(input.width);
+                        presenter.set_cursor(Position { line, offset });
+                    }
+                }
 
                 presenter.present()?;
             } else {
@@ -76,16 +79,18 @@ impl ModeRenderer for SearchRenderer {
 
 #[derive(Debug)]
 pub struct SearchData {
-    pub search_string: String,
+    pub input: LineEditor,
+    pub history: InputHistory,
     pub is_exec_search: bool,
     pub search_result_index: usize,
     pub search_result: Vec<Range>,
 }
 
 impl SearchData {
-    pub fn new() -> Self {
+    pub fn new(history: InputHistory) -> Self {
         Self {
-            search_string: String::new(),
+            input: LineEditor::default(),
+            history,
             is_exec_search: false,
             search_result_index: 0,
             search_result: Vec::new(),
@@ -93,7 +98,8 @@ impl SearchData {
     }
 
     pub fn clear(&mut self) {
-        self.search_string.clear();
+        self.input.clear();
+        self.history.reset();
         self.is_exec_search = false;
         self.search_result_index = 0;
         self.search_result.clear();
@@ -0,0 +1,65 @@
+use held_core::{
+    utils::{position::Position, range::Range},
+    view::{colors::Colors, style::CharStyle},
+};
+
+use super::{ModeData, ModeRenderer};
+use crate::{errors::*, view::status_line::StatusLine};
+
+/// 选择模式：选区为锚点与光标之间的内容，包含两端的字符
+#[derive(Debug, Default)]
+pub struct SelectData {
+    pub anchor: Position,
+}
+
+impl SelectData {
+    pub fn range(&self, cursor: Position) -> Range {
+        let (start, end) = if self.anchor <= cursor {
+            (self.anchor, cursor)
+        } else {
+            (cursor, self.anchor)
+        };
+        Range::new(
+            start,
+            Position {
+                line: end.line,
+                offset: end.offset + 1,
+            },
+        )
+    }
+}
+
+pub(super) struct SelectRenderer;
+
+impl ModeRenderer for SelectRenderer {
+    fn render(
+        workspace: &mut crate::workspace::Workspace,
+        monitor: &mut crate::view::monitor::Monitor,
+        mode: &mut super::ModeData,
+    ) -> Result<()> {
+        let mut presenter = monitor.build_presenter()?;
+
+        if let (Some(buffer), ModeData::Select(select_data)) = (&workspace.current_buffer, mode) {
+            let data = buffer.data();
+            let highlights = [(
+                select_data.range(*buffer.cursor),
+                CharStyle::Default,
+                Colors::SelectMode,
+            )];
+            presenter.print_buffer(
+                buffer,
+                &data,
+                &workspace.syntax_set,
+                Some(&highlights),
+                None,
+            )?;
+
+            presenter
+                .print_status_line(StatusLine::new("SELECT").buffer(&workspace.current_buffer))?;
+
+            presenter.present()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,17 +1,18 @@
 use held_core::interface::app::App;
 
-use crate::application::Application;
+use super::call_handler;
+use crate::application::{handler::app, Application};
 
 impl App for Application {
     fn exit(&mut self) {
-        todo!()
+        call_handler(self, app::exit)
     }
 
     fn to_insert_mode(&mut self) {
-        todo!()
+        call_handler(self, app::to_insert_mode)
     }
 
     fn to_normal_mode(&mut self) {
-        todo!()
+        call_handler(self, app::to_normal_mode)
     }
 }
@@ -1,17 +1,18 @@
 use held_core::interface;
 
-use crate::application::Application;
+use super::call_handler;
+use crate::application::{handler::buffer, Application};
 
 impl interface::buffer::Buffer for Application {
     fn insert_char(&mut self) {
-        todo!()
+        call_handler(self, buffer::insert_char)
     }
 
     fn new_line(&mut self) {
-        todo!()
+        call_handler(self, buffer::new_line)
     }
 
     fn insert_tab(&mut self) {
-        todo!()
+        call_handler(self, buffer::insert_tab)
     }
 }
@@ -1,29 +1,38 @@
 use held_core::interface;
 
-use crate::application::Application;
+use super::call_handler;
+use crate::application::{handler::cursor, Application};
 
 impl interface::cursor::Cursor for Application {
     fn move_left(&mut self) {
-        todo!()
+        call_handler(self, cursor::move_left)
     }
 
     fn move_right(&mut self) {
-        todo!()
+        call_handler(self, cursor::move_right)
     }
 
     fn move_up(&mut self) {
-        todo!()
+        call_handler(self, cursor::move_up)
     }
 
     fn move_down(&mut self) {
-        todo!()
+        call_handler(self, cursor::move_down)
     }
 
     fn move_to_start_of_line(&mut self) {
-        todo!()
+        call_handler(self, cursor::move_to_start_of_line)
     }
 
     fn screen_cursor_position(&self) -> held_core::utils::position::Position {
         self.state_data.cursor_state.screen_position
     }
+
+    fn cursor_position(&self) -> held_core::utils::position::Position {
+        self.workspace
+            .current_buffer
+            .as_ref()
+            .map(|buffer| buffer.cursor.position)
+            .unwrap_or_default()
+    }
 }
@@ -1,6 +1,7 @@
-use held_core::interface::{app::App, ApplicationInterface};
+use held_core::interface::ApplicationInterface;
 
 use super::Application;
+use crate::errors::*;
 
 pub mod app;
 pub mod buffer;
@@ -9,3 +10,10 @@ pub mod monitor;
 pub mod workspace;
 
 impl ApplicationInterface for Application {}
+
+// 插件通过接口调用的处理函数，出错时与按键触发时一样显示在状态栏上
+fn call_handler(app: &mut Application, handler: fn(&mut Application) -> Result<()>) {
+    if let Err(e) = handler(app) {
+        app.messages.error(&e);
+    }
+}
@@ -1,13 +1,14 @@
 use held_core::interface;
 
-use crate::application::Application;
+use super::call_handler;
+use crate::application::{handler::monitor, Application};
 
 impl interface::monitor::Monitor for Application {
     fn scroll_to_cursor(&mut self) {
-        todo!()
+        call_handler(self, monitor::scroll_to_cursor)
     }
 
     fn scroll_to_center(&mut self) {
-        todo!()
+        call_handler(self, monitor::scroll_to_center)
     }
 }
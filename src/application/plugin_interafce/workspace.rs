@@ -1,13 +1,14 @@
 use held_core::interface;
 
-use crate::application::Application;
+use super::call_handler;
+use crate::application::{handler::buffer, Application};
 
 impl interface::workspace::Workspace for Application {
     fn save_file(&mut self) {
-        todo!()
+        call_handler(self, buffer::save_file)
     }
 
     fn undo(&mut self) {
-        todo!()
+        call_handler(self, buffer::undo)
     }
 }
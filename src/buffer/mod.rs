@@ -29,8 +29,11 @@ pub struct Buffer {
     pub cursor: Cursor,
     history: History,
     operation_group: Option<OperationGroup>,
+    operation_group_depth: usize,
     pub syntax_definition: Option<SyntaxReference>,
     pub change_callback: Option<Box<dyn Fn(Position)>>,
+    // 每次产生新的修改操作时递增，撤销与重做不计入
+    revision: usize,
 }
 
 impl Default for Buffer {
@@ -45,10 +48,12 @@ impl Default for Buffer {
             data: data.clone(),
             path: None,
             cursor,
-            history: History::new(),
+            history,
             operation_group: None,
+            operation_group_depth: 0,
             syntax_definition: None,
             change_callback: None,
+            revision: 0,
         }
     }
 }
@@ -60,24 +65,32 @@ impl Buffer {
 
     pub fn from_file(path: &Path) -> io::Result<Buffer> {
         let content = fs::read_to_string(path)?;
+        let mut buffer = Buffer::from_content(content);
+        buffer.path = Some(path.canonicalize()?);
+        Ok(buffer)
+    }
 
+    /// 不对应任何文件的buffer，内容视为未修改
+    pub fn from_content(content: String) -> Buffer {
         let data = Rc::new(RefCell::new(GapBuffer::new(content)));
         let cursor = Cursor::new(data.clone(), Position { line: 0, offset: 0 });
 
         let mut buffer = Buffer {
             id: None,
             data: data.clone(),
-            path: Some(path.canonicalize()?),
+            path: None,
             cursor,
             history: History::new(),
             operation_group: None,
+            operation_group_depth: 0,
             syntax_definition: None,
             change_callback: None,
+            revision: 0,
         };
 
         buffer.history.mark();
 
-        Ok(buffer)
+        buffer
     }
 
     pub fn data(&self) -> String {
@@ -98,6 +111,11 @@ impl Buffer {
         Ok(())
     }
 
+    /// 将当前内容视为未修改，用于不需要保存的buffer
+    pub fn mark_unmodified(&mut self) {
+        self.history.mark();
+    }
+
     pub fn file_name(&self) -> Option<String> {
         self.path.as_ref().and_then(|p| {
             p.file_name()
@@ -119,6 +137,11 @@ impl Buffer {
             None => self.history.previous(),
         };
 
+        // 仍处于操作组中（例如插入模式下撤销），之后的修改继续分组
+        if self.operation_group_depth > 0 {
+            self.operation_group = Some(OperationGroup::new());
+        }
+
         // If we found an eligible operation, reverse it.
         if let Some(mut op) = operation {
             op.reverse(self);
@@ -159,6 +182,10 @@ impl Buffer {
         results
     }
 
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
     pub fn modified(&self) -> bool {
         !self.history.at_mark()
     }
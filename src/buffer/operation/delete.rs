@@ -73,9 +73,6 @@ impl Buffer {
 
         // Store the operation in the history
         // object so that it can be undone.
-        match self.operation_group {
-            Some(ref mut group) => group.add(Box::new(op)),
-            None => self.history.add(Box::new(op)),
-        };
+        self.add_operation(Box::new(op));
     }
 }
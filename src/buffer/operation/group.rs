@@ -47,8 +47,9 @@ impl OperationGroup {
 }
 
 impl Buffer {
-    // 开启一个操作组
+    // 开启一个操作组，操作组可以嵌套，只有最外层结束时才会写入历史
     pub fn start_operation_group(&mut self) {
+        self.operation_group_depth += 1;
         // Create an operation group, if one doesn't already exist.
         match self.operation_group {
             Some(_) => (),
@@ -58,7 +59,31 @@ impl Buffer {
         }
     }
 
+    // 在最外层操作组内设置撤销断点：之前的操作成为一个独立的撤销单元
+    pub fn break_operation_group(&mut self) {
+        if self.operation_group_depth == 1 {
+            self.push_operation_group();
+            self.operation_group = Some(OperationGroup::new());
+        }
+    }
+
+    // 记录一个已经执行的操作，操作组打开时加入操作组
+    pub(crate) fn add_operation(&mut self, operation: Box<dyn Operation>) {
+        self.revision += 1;
+        match self.operation_group {
+            Some(ref mut group) => group.add(operation),
+            None => self.history.add(operation),
+        };
+    }
+
     pub fn end_operation_group(&mut self) {
+        self.operation_group_depth = self.operation_group_depth.saturating_sub(1);
+        if self.operation_group_depth == 0 {
+            self.push_operation_group();
+        }
+    }
+
+    fn push_operation_group(&mut self) {
         // Push an open operation group on to the history stack, if one exists.
         if let Some(group) = self.operation_group.take() {
             if !group.is_empty() {
@@ -67,3 +92,29 @@ impl Buffer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::Buffer;
+
+    #[test]
+    fn nested_groups_are_one_undo_step() {
+        let mut buffer = Buffer::default();
+        buffer.start_operation_group();
+        buffer.insert("a");
+        buffer.break_operation_group();
+        buffer.insert("b");
+        buffer.start_operation_group();
+        buffer.insert("c");
+        // 内层的断点与结束不会拆分外层操作组
+        buffer.break_operation_group();
+        buffer.end_operation_group();
+        buffer.insert("d");
+        buffer.end_operation_group();
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "a");
+        buffer.undo();
+        assert_eq!(buffer.data(), "");
+    }
+}
@@ -77,10 +77,7 @@ impl Buffer {
         op.run(self);
 
         // Store the operation in the history object so that it can be undone.
-        match self.operation_group {
-            Some(ref mut group) => group.add(Box::new(op)),
-            None => self.history.add(Box::new(op)),
-        };
+        self.add_operation(Box::new(op));
     }
 
     pub fn replace_on_cursor<T: Into<String> + AsRef<str>>(&mut self, content: T) {
@@ -1,16 +1,55 @@
+use std::{fs, path::PathBuf};
+
 use clap::Parser;
 use log::LevelFilter;
 
+use crate::errors::*;
+
 #[derive(Parser)]
 #[command(name = "held")]
 #[command(author = "heyicong@dragonos.org")]
 #[command(version = "1.0")]
 #[command(about = "a termial editor", long_about = None)]
 pub struct CmdConfig {
-    /// open file
-    pub file: Option<String>,
+    /// open files
+    pub files: Vec<String>,
 
     /// log level
     #[arg(value_enum, short, long, default_value = "warn")]
     pub level: LevelFilter,
+
+    /// execute a command after loading the files without ui, can be repeated
+    #[arg(short = 'c', long = "command", value_name = "COMMAND")]
+    pub commands: Vec<String>,
+
+    /// execute commands from a script file (one per line) without ui
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+}
+
+impl CmdConfig {
+    /// 是否以无界面模式运行
+    pub fn headless(&self) -> bool {
+        !self.commands.is_empty() || self.script.is_some()
+    }
+
+    /// 脚本文件中的命令在前，`-c` 指定的命令在后
+    ///
+    /// 脚本中的空行与以 `"` 开头的注释行会被忽略。
+    pub fn script_commands(&self) -> Result<Vec<String>> {
+        let mut commands = Vec::new();
+        if let Some(script) = &self.script {
+            let content = fs::read_to_string(script)
+                .chain_err(|| format!("Couldn't read script {}", script.display()))?;
+            commands.extend(
+                content
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('"'))
+                    .map(|line| line.to_string()),
+            );
+        }
+        commands.extend(self.commands.iter().cloned());
+        Ok(commands)
+    }
 }
@@ -1,9 +1,10 @@
-use std::env;
+use std::{env, process};
 
 use application::Application;
 use clap::Parser;
 use config::CmdConfig;
 use util::log_util::Log;
+use view::terminal::guard::TerminalGuard;
 
 mod application;
 mod buffer;
@@ -28,9 +29,19 @@ pub fn get_application() -> &'static mut Application {
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
     let config = CmdConfig::parse();
     Log::init(config.level)?;
+    let _terminal_guard = TerminalGuard::install();
+
+    // 工作区只关心程序名与待打开的文件
+    let args: Vec<String> = env::args()
+        .take(1)
+        .chain(config.files.iter().cloned())
+        .collect();
+
+    if config.headless() {
+        process::exit(run_headless(&config, &args));
+    }
 
     let application = Application::new(&args)?;
 
@@ -40,3 +51,19 @@ fn main() -> Result<()> {
 
     get_application().run()
 }
+
+// 无界面模式下的错误直接输出到stderr并以非0状态退出
+fn run_headless(config: &CmdConfig, args: &[String]) -> i32 {
+    let result = config.script_commands().and_then(|commands| {
+        let application = Application::headless(args)?;
+        unsafe {
+            APPLICATION = Some(application);
+        }
+        get_application().run_script(&commands)
+    });
+
+    result.unwrap_or_else(|e| {
+        eprintln!("held: {}", e);
+        1
+    })
+}
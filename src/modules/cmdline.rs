@@ -0,0 +1,291 @@
+use std::{
+    fs::{read_to_string, write},
+    path::PathBuf,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::errors::*;
+
+// 每类输入保留的历史条数
+const MAX_HISTORY: usize = 100;
+
+/// 单行输入的文本与光标，命令行与搜索共用
+///
+/// 光标为文本中的字节偏移，总是位于字素簇的边界上。
+#[derive(Debug, Default, Clone)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// 光标之前的文本
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// 替换全部文本，光标移到末尾
+    pub fn set<T: Into<String>>(&mut self, text: T) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// 将start到光标之间的文本替换为replacement
+    pub fn replace_before_cursor(&mut self, start: usize, replacement: &str) {
+        self.text.replace_range(start..self.cursor, replacement);
+        self.cursor = start + replacement.len();
+    }
+
+    /// 删除光标前的字符，没有可以删除的字符时返回false
+    pub fn backspace(&mut self) -> bool {
+        match self.previous_boundary() {
+            Some(start) => {
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 删除光标处的字符
+    pub fn delete(&mut self) {
+        if let Some(end) = self.next_boundary() {
+            self.text.replace_range(self.cursor..end, "");
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(start) = self.previous_boundary() {
+            self.cursor = start;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(end) = self.next_boundary() {
+            self.cursor = end;
+        }
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// `ctrl-w` 删除光标前的一个单词及其后的空白
+    pub fn delete_word(&mut self) {
+        let before = self.before_cursor();
+        let trimmed = before.trim_end();
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = match trimmed.chars().next_back() {
+            Some(last) if is_word(last) => trimmed
+                .char_indices()
+                .rev()
+                .find(|(_, c)| !is_word(*c))
+                .map_or(0, |(index, c)| index + c.len_utf8()),
+            // 连续的符号视为一个单词
+            Some(_) => trimmed
+                .char_indices()
+                .rev()
+                .find(|(_, c)| is_word(*c) || c.is_whitespace())
+                .map_or(0, |(index, c)| index + c.len_utf8()),
+            None => 0,
+        };
+        self.replace_before_cursor(start, "");
+    }
+
+    /// `ctrl-u` 删除光标前的所有文本
+    pub fn delete_to_start(&mut self) {
+        self.replace_before_cursor(0, "");
+    }
+
+    fn previous_boundary(&self) -> Option<usize> {
+        self.before_cursor()
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(index, _)| index)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| self.cursor + grapheme.len())
+    }
+}
+
+/// 输入历史
+///
+/// 每类输入的历史按行保存在历史目录下同名的文件中。
+/// 浏览历史时只显示以开始浏览前已输入的文本为前缀的记录。
+#[derive(Debug, Default)]
+pub struct InputHistory {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+    // 正在查看的记录，None表示正在编辑新的输入
+    index: Option<usize>,
+    // 开始浏览前已输入的文本
+    draft: String,
+}
+
+impl InputHistory {
+    pub fn load(history_path: PathBuf, name: &str) -> Result<InputHistory> {
+        let mut history = InputHistory::default();
+        #[cfg(feature = "dragonos")]
+        let _ = (history_path, name);
+        #[cfg(not(feature = "dragonos"))]
+        {
+            let path = history_path.join(name);
+            if path.is_file() {
+                history.entries = read_to_string(&path)
+                    .chain_err(|| format!("Couldn't read history file: {:?}", path))?
+                    .lines()
+                    .filter(|entry| !entry.is_empty())
+                    .map(|entry| entry.to_string())
+                    .collect();
+            }
+            history.path = Some(path);
+        }
+
+        Ok(history)
+    }
+
+    /// 记录一条输入，相同的旧记录被移到最后
+    pub fn push(&mut self, entry: &str) -> Result<()> {
+        self.reset();
+        if entry.is_empty() || entry.contains('\n') {
+            return Ok(());
+        }
+        self.entries.retain(|e| e != entry);
+        self.entries.push(entry.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.drain(..self.entries.len() - MAX_HISTORY);
+        }
+        self.save()
+    }
+
+    /// 停止浏览历史，回到编辑新的输入
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.draft.clear();
+    }
+
+    /// 上一条记录，current为当前输入的文本
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let end = match self.index {
+            Some(index) => index,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len()
+            }
+        };
+        let index = (0..end)
+            .rev()
+            .find(|index| self.entries[*index].starts_with(&self.draft))?;
+        self.index = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// 下一条记录，越过最新的记录时回到开始浏览前输入的文本
+    pub fn next(&mut self) -> Option<&str> {
+        let start = self.index? + 1;
+        match (start..self.entries.len())
+            .find(|index| self.entries[*index].starts_with(&self.draft))
+        {
+            Some(index) => {
+                self.index = Some(index);
+                Some(&self.entries[index])
+            }
+            None => {
+                self.index = None;
+                Some(&self.draft)
+            }
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut out = self.entries.join("\n");
+        out.push('\n');
+        write(path, out).chain_err(|| format!("Couldn't write history file: {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputHistory, LineEditor};
+
+    #[test]
+    fn editing_at_the_cursor() {
+        let mut editor = LineEditor::default();
+        editor.set("s/foo.bar baz");
+        editor.move_left();
+        editor.move_left();
+        editor.insert_char('X');
+        assert_eq!(editor.text(), "s/foo.bar bXaz");
+
+        editor.move_to_end();
+        editor.delete_word();
+        assert_eq!(editor.text(), "s/foo.bar ");
+        editor.delete_word();
+        assert_eq!(editor.text(), "s/foo.");
+        editor.delete_word();
+        assert_eq!(editor.text(), "s/foo");
+
+        editor.move_to_start();
+        editor.delete();
+        editor.move_right();
+        editor.delete_to_start();
+        assert_eq!(editor.text(), "foo");
+        assert_eq!(editor.cursor(), 0);
+        assert!(!editor.backspace());
+    }
+
+    #[test]
+    fn history_is_filtered_by_the_typed_prefix() {
+        let mut history = InputHistory::default();
+        for entry in ["w", "set tab_width=2", "wq", "w"] {
+            history.push(entry).unwrap();
+        }
+
+        assert_eq!(history.previous("w"), Some("w"));
+        assert_eq!(history.previous("w"), Some("wq"));
+        assert_eq!(history.previous("w"), None);
+        assert_eq!(history.next(), Some("w"));
+        assert_eq!(history.next(), Some("w"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous(""), Some("w"));
+        assert_eq!(history.previous(""), Some("wq"));
+        assert_eq!(history.previous(""), Some("set tab_width=2"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use walkdir::WalkDir;
+
+// 一次最多列出的文件数，避免在过大的目录中卡住
+const MAX_FILES: usize = 50000;
+
+/// 按配置的忽略规则与 `.gitignore` 过滤工作区中的文件
+///
+/// 配置的规则（如 `.git`、`target`）与 `.gitignore` 的写法相同，
+/// 每个目录下的 `.gitignore` 在第一次经过该目录时读取，深层目录的规则优先。
+pub struct FileFilter {
+    root: PathBuf,
+    configured: Gitignore,
+    gitignores: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl FileFilter {
+    pub fn new(root: &Path, patterns: &[String]) -> FileFilter {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                warn!("Invalid ignore pattern {pattern:?}: {e}");
+            }
+        }
+
+        FileFilter {
+            root: root.to_path_buf(),
+            configured: builder.build().unwrap_or_else(|_| Gitignore::empty()),
+            gitignores: HashMap::new(),
+        }
+    }
+
+    /// path是否被忽略，is_dir表示path是否为目录
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        if path.starts_with(self.root.join(".git"))
+            || self.configured.matched(path, is_dir).is_ignore()
+        {
+            return true;
+        }
+
+        // 从最深的目录开始匹配，第一条命中的规则生效
+        let dirs: Vec<PathBuf> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .map(|dir| dir.to_path_buf())
+            .collect();
+        for dir in dirs {
+            if let Some(gitignore) = self.gitignore(&dir) {
+                match gitignore.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+        false
+    }
+
+    /// 工作区下所有未被忽略的文件，按路径排序
+    pub fn walk(&mut self) -> Vec<PathBuf> {
+        let root = self.root.clone();
+        WalkDir::new(&root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !self.is_ignored(entry.path(), entry.file_type().is_dir())
+            })
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .take(MAX_FILES)
+            .collect()
+    }
+
+    fn gitignore(&mut self, dir: &Path) -> Option<&Gitignore> {
+        self.gitignores
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let path = dir.join(".gitignore");
+                if !path.is_file() {
+                    return None;
+                }
+                let (gitignore, error) = Gitignore::new(&path);
+                if let Some(e) = error {
+                    warn!("Couldn't fully parse {path:?}: {e}");
+                }
+                Some(gitignore)
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::FileFilter;
+
+    #[test]
+    fn configured_patterns_and_gitignore_are_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/gen")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "gen/\n!keep.log\n").unwrap();
+        for file in [
+            "a.rs",
+            "debug.log",
+            "src/main.rs",
+            "src/keep.log",
+            "src/gen/out.rs",
+            "target/bin",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut filter = FileFilter::new(root, &["target".to_string()]);
+        let files: Vec<String> = filter
+            .walk()
+            .iter()
+            .map(|path| {
+                path.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+
+        assert_eq!(
+            files,
+            [
+                ".gitignore",
+                "a.rs",
+                "src/.gitignore",
+                "src/keep.log",
+                "src/main.rs"
+            ]
+        );
+    }
+}
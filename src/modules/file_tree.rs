@@ -0,0 +1,441 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    ffi::OsStr,
+    fs,
+    os::unix::fs::{DirEntryExt, MetadataExt},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::SystemTime,
+};
+
+use super::file_filter::FileFilter;
+
+/// 目录树中的一行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub is_dir: bool,
+    pub is_open: bool,
+    pub ino: u64,
+}
+
+/// 工作区的目录树
+///
+/// 展开的目录按ino记录，重命名后仍保持展开。遍历的结果被缓存，
+/// 只有在展开状态改变、被标记为需要更新或展开的目录内容改变时才重新遍历。
+/// 按配置与 `.gitignore` 忽略的路径不显示，以 `.` 开头的路径只在显示隐藏文件时显示。
+/// 过滤时在后台线程中遍历工作区，遍历完成之前没有匹配的文件。
+#[derive(Default)]
+pub struct FileTree {
+    root: PathBuf,
+    ignore_patterns: Vec<String>,
+    file_filter: Option<FileFilter>,
+    show_hidden: bool,
+    opened_dir_inos: HashSet<u64>,
+    entries: Vec<TreeEntry>,
+    selected: usize,
+    selected_path: PathBuf,
+    // 上次遍历时各展开目录的修改时间
+    dir_mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+    dirty: bool,
+    // 按文件名过滤时的输入、工作区中所有未被忽略的文件、匹配的文件与其上级目录
+    query: String,
+    all_files: Option<Vec<PathBuf>>,
+    walking: Option<Receiver<Vec<PathBuf>>>,
+    matched: HashSet<PathBuf>,
+    shown: HashSet<PathBuf>,
+}
+
+impl FileTree {
+    pub fn new(root: &Path, ignore_patterns: Vec<String>) -> FileTree {
+        let mut opened_dir_inos = HashSet::new();
+        if let Ok(metadata) = root.metadata() {
+            opened_dir_inos.insert(metadata.ino());
+        }
+        FileTree {
+            root: root.to_path_buf(),
+            file_filter: Some(FileFilter::new(root, &ignore_patterns)),
+            ignore_patterns,
+            opened_dir_inos,
+            selected_path: root.to_path_buf(),
+            dirty: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn entries(&self) -> &[TreeEntry] {
+        &self.entries
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_path(&self) -> &Path {
+        &self.selected_path
+    }
+
+    /// 下次更新时重新遍历
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn set_show_hidden(&mut self, show_hidden: bool) {
+        if self.show_hidden != show_hidden {
+            self.show_hidden = show_hidden;
+            self.invalidate();
+        }
+    }
+
+    /// 是否正在后台遍历工作区
+    pub fn is_walking(&self) -> bool {
+        self.walking.is_some()
+    }
+
+    /// 当前的过滤输入，为空时不过滤
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// 只显示文件名包含query（不区分大小写）的文件与其上级目录，匹配的目录全部展开
+    pub fn set_query(&mut self, query: &str) {
+        if self.query == query {
+            return;
+        }
+        if query.is_empty() {
+            self.discard_files();
+        }
+        self.query = query.to_string();
+        self.invalidate();
+    }
+
+    /// path是否匹配过滤输入
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.matched.contains(path)
+    }
+
+    /// 需要时重新遍历，返回是否重新遍历了目录
+    pub fn update(&mut self) -> bool {
+        self.receive_files();
+        let changed = self
+            .dir_mtimes
+            .iter()
+            .any(|(dir, mtime)| modified_time(dir) != *mtime);
+        if !self.dirty && !changed {
+            return false;
+        }
+
+        self.entries.clear();
+        self.dir_mtimes.clear();
+        self.update_matches();
+        if let Ok(metadata) = self.root.metadata() {
+            let root = self.root.clone();
+            self.push_entry(root, 0, true, metadata.ino());
+        }
+        self.dirty = false;
+
+        // 按路径找回所选的行，该行已不存在（如被删除）时选择同一位置的行；
+        // 过滤时所选的不是匹配的文件则选择第一个匹配的文件
+        let position = |path: &Path| self.entries.iter().position(|entry| entry.path == path);
+        let first_match = || {
+            self.entries
+                .iter()
+                .position(|entry| self.matched.contains(&entry.path))
+        };
+        self.selected = match position(&self.selected_path) {
+            Some(index) if self.query.is_empty() || self.matched.contains(&self.selected_path) => {
+                index
+            }
+            Some(index) => first_match().unwrap_or(index),
+            None => first_match()
+                .unwrap_or_else(|| self.selected.min(self.entries.len().saturating_sub(1))),
+        };
+        if let Some(entry) = self.entries.get(self.selected) {
+            self.selected_path = entry.path.clone();
+        }
+        true
+    }
+
+    /// 选择第index行，返回该行是否存在
+    pub fn select(&mut self, index: usize) -> bool {
+        match self.entries.get(index) {
+            Some(entry) => {
+                self.selected = index;
+                self.selected_path = entry.path.clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_selection(&mut self, forward: bool) {
+        let index = match forward {
+            true => self.selected + 1,
+            false => self.selected.saturating_sub(1),
+        };
+        self.select(index);
+    }
+
+    /// 展开或收起所选的目录，所选的是文件时返回其路径
+    pub fn toggle_selected(&mut self) -> Option<PathBuf> {
+        let entry = self.entries.get(self.selected)?;
+        if !entry.is_dir {
+            return Some(entry.path.clone());
+        }
+        if !self.opened_dir_inos.remove(&entry.ino) {
+            self.opened_dir_inos.insert(entry.ino);
+        }
+        self.invalidate();
+        None
+    }
+
+    /// 展开path所在的各级目录并选择path
+    pub fn reveal(&mut self, path: &Path) {
+        if !path.starts_with(&self.root) {
+            return;
+        }
+        for dir in path.ancestors().skip(1) {
+            if let Ok(metadata) = dir.metadata() {
+                self.opened_dir_inos.insert(metadata.ino());
+            }
+            if dir == self.root {
+                break;
+            }
+        }
+        self.selected_path = path.to_path_buf();
+        self.invalidate();
+    }
+
+    /// 重新遍历并重新读取 `.gitignore`，丢弃已不存在的目录的展开状态
+    pub fn refresh(&mut self) {
+        self.file_filter = Some(FileFilter::new(&self.root, &self.ignore_patterns));
+        self.discard_files();
+        let removed: HashSet<u64> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_dir && !entry.path.is_dir())
+            .map(|entry| entry.ino)
+            .collect();
+        self.opened_dir_inos.retain(|ino| !removed.contains(ino));
+        self.invalidate();
+    }
+
+    // 丢弃遍历的结果，正在进行的遍历的结果同样不再使用
+    fn discard_files(&mut self) {
+        self.all_files = None;
+        self.walking = None;
+    }
+
+    // 在后台线程中遍历工作区中的所有文件
+    fn walk_files(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        let mut filter = FileFilter::new(&self.root, &self.ignore_patterns);
+        thread::spawn(move || {
+            let _ = sender.send(filter.walk());
+        });
+        self.walking = Some(receiver);
+    }
+
+    // 遍历完成后重新匹配
+    fn receive_files(&mut self) {
+        let files = match self.walking.as_ref().map(Receiver::try_recv) {
+            Some(Ok(files)) => files,
+            Some(Err(TryRecvError::Disconnected)) => Vec::new(),
+            Some(Err(TryRecvError::Empty)) | None => return,
+        };
+        self.all_files = Some(files);
+        self.walking = None;
+        self.invalidate();
+    }
+
+    // 过滤时找出匹配的文件，工作区中的文件只在开始过滤时遍历一次
+    fn update_matches(&mut self) {
+        self.matched.clear();
+        self.shown.clear();
+        if self.query.is_empty() {
+            return;
+        }
+
+        if self.all_files.is_none() && !self.is_walking() {
+            self.walk_files();
+        }
+        let query = self.query.to_lowercase();
+        for path in self.all_files.iter().flatten() {
+            let relative = path.strip_prefix(&self.root).unwrap_or(path);
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if (!self.show_hidden && is_hidden(relative)) || !name.to_lowercase().contains(&query) {
+                continue;
+            }
+            self.matched.insert(path.clone());
+            for dir in path.ancestors().skip(1) {
+                if !self.shown.insert(dir.to_path_buf()) || dir == self.root {
+                    break;
+                }
+            }
+        }
+        self.shown.extend(self.matched.iter().cloned());
+    }
+
+    // 未过滤时跳过被忽略的与隐藏的路径，过滤时只保留匹配的文件与其上级目录
+    fn is_visible(&mut self, path: &Path, is_dir: bool) -> bool {
+        if !self.query.is_empty() {
+            return self.shown.contains(path);
+        }
+        if !self.show_hidden && path.file_name().is_some_and(is_hidden_name) {
+            return false;
+        }
+        match self.file_filter {
+            Some(ref mut filter) => !filter.is_ignored(path, is_dir),
+            None => true,
+        }
+    }
+
+    fn push_entry(&mut self, path: PathBuf, depth: usize, is_dir: bool, ino: u64) {
+        let is_open = is_dir
+            && match self.query.is_empty() {
+                true => self.opened_dir_inos.contains(&ino),
+                false => self.shown.contains(&path),
+            };
+        self.entries.push(TreeEntry {
+            path: path.clone(),
+            depth,
+            is_dir,
+            is_open,
+            ino,
+        });
+        if is_open {
+            self.push_children(&path, depth + 1);
+        }
+    }
+
+    // 目录在前，同类按名字排序
+    fn push_children(&mut self, dir: &Path, depth: usize) {
+        self.dir_mtimes
+            .push((dir.to_path_buf(), modified_time(dir)));
+        let mut children: Vec<(PathBuf, bool, u64)> = match fs::read_dir(dir) {
+            Ok(read_dir) => read_dir
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                    (entry.path(), is_dir, entry.ino())
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        children.retain(|(path, is_dir, _)| self.is_visible(path, *is_dir));
+        children.sort_by(|(a, a_dir, _), (b, b_dir, _)| match (a_dir, b_dir) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => a.file_name().cmp(&b.file_name()),
+        });
+
+        for (path, is_dir, ino) in children {
+            self.push_entry(path, depth, is_dir, ino);
+        }
+    }
+}
+
+fn is_hidden_name(name: &OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+// 相对路径中是否有以 `.` 开头的部分
+fn is_hidden(relative: &Path) -> bool {
+    relative
+        .components()
+        .any(|component| is_hidden_name(component.as_os_str()))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use super::FileTree;
+
+    #[test]
+    fn walks_only_when_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("README"), "").unwrap();
+
+        let mut tree = FileTree::new(root, Vec::new());
+        assert!(tree.update());
+        assert!(!tree.update());
+        let names = |tree: &FileTree| -> Vec<String> {
+            tree.entries()
+                .iter()
+                .skip(1)
+                .map(|entry| {
+                    let name = entry.path.strip_prefix(root).unwrap();
+                    format!("{}{}", " ".repeat(entry.depth), name.to_string_lossy())
+                })
+                .collect()
+        };
+        assert_eq!(names(&tree), [" src", " README"]);
+
+        tree.reveal(&root.join("src/main.rs"));
+        assert!(tree.update());
+        assert_eq!(names(&tree), [" src", "  src/main.rs", " README"]);
+        assert_eq!(tree.selected(), 2);
+
+        fs::remove_file(root.join("src/main.rs")).unwrap();
+        assert!(tree.update());
+        assert_eq!(names(&tree), [" src", " README"]);
+        assert_eq!(tree.selected(), 2);
+    }
+
+    #[test]
+    fn hides_ignored_and_hidden_entries_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/deep")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        for file in [
+            ".gitignore",
+            ".env",
+            "a.log",
+            "src/deep/util.rs",
+            "src/main.rs",
+        ] {
+            fs::write(root.join(file), "*.log\n").unwrap();
+        }
+
+        let mut tree = FileTree::new(root, vec!["target".to_string()]);
+        let names = |tree: &mut FileTree| -> Vec<String> {
+            tree.update();
+            tree.entries()
+                .iter()
+                .skip(1)
+                .map(|entry| entry.path.strip_prefix(root).unwrap())
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(names(&mut tree), ["src"]);
+        tree.set_show_hidden(true);
+        assert_eq!(names(&mut tree), ["src", ".env", ".gitignore"]);
+
+        tree.set_query("UTIL");
+        assert_eq!(names(&mut tree), Vec::<String>::new());
+        while tree.is_walking() {
+            thread::sleep(Duration::from_millis(10));
+            tree.update();
+        }
+        assert_eq!(names(&mut tree), ["src", "src/deep", "src/deep/util.rs"]);
+        assert_eq!(tree.selected(), 3);
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::SystemTime,
+};
+
+/// 文件在git仓库中的状态，目录取其中的文件最重要的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitStatus {
+    Untracked,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+// 一次 `git status` 的结果与其完成后暂存区的修改时间
+#[derive(Debug, Default)]
+struct Snapshot {
+    statuses: HashMap<PathBuf, GitStatus>,
+    // 整个目录都未被跟踪时 `git status` 只列出该目录
+    untracked_dirs: HashSet<PathBuf>,
+    index_mtime: Option<SystemTime>,
+}
+
+/// 工作区中各文件的git状态，通过本地的 `git status` 读取
+///
+/// `git status` 在后台线程中运行，完成之前保留上一次的结果，由 `poll` 取回新的结果。
+/// 工作区不在git仓库中或没有安装git时没有任何状态。
+#[derive(Debug, Default)]
+pub struct GitStatuses {
+    root: PathBuf,
+    // 工作区相对于仓库根目录的路径与仓库的 `.git` 目录
+    repository: Option<(String, PathBuf)>,
+    snapshot: Snapshot,
+    running: Option<Receiver<Snapshot>>,
+    // 运行期间又请求了刷新，完成后需要再读取一次
+    stale: bool,
+}
+
+impl GitStatuses {
+    pub fn new(root: &Path) -> GitStatuses {
+        let repository =
+            git(root, &["rev-parse", "--show-prefix", "--git-dir"]).and_then(|output| {
+                let mut lines = output.lines();
+                let prefix = lines.next()?.to_string();
+                let git_dir = root.join(lines.next()?);
+                Some((prefix, git_dir))
+            });
+        GitStatuses {
+            root: root.to_path_buf(),
+            repository,
+            ..Default::default()
+        }
+    }
+
+    /// path的状态，未被跟踪的目录中的文件同样未被跟踪
+    pub fn get(&self, path: &Path) -> Option<GitStatus> {
+        let snapshot = &self.snapshot;
+        snapshot.statuses.get(path).copied().or_else(|| {
+            path.ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(&self.root))
+                .any(|dir| snapshot.untracked_dirs.contains(dir))
+                .then_some(GitStatus::Untracked)
+        })
+    }
+
+    /// 是否有 `git status` 正在运行
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// 暂存区在上次读取后是否改变，如执行了 `git add` 或 `git commit`
+    pub fn index_changed(&self) -> bool {
+        match self.repository {
+            Some((_, ref git_dir)) if !self.is_running() => {
+                modified_time(&git_dir.join("index")) != self.snapshot.index_mtime
+            }
+            _ => false,
+        }
+    }
+
+    /// 在后台重新读取各文件的状态
+    pub fn refresh(&mut self) {
+        let (prefix, git_dir) = match self.repository {
+            Some(ref repository) => repository.clone(),
+            None => return,
+        };
+        if self.is_running() {
+            self.stale = true;
+            return;
+        }
+
+        let root = self.root.clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut snapshot = git(&root, &["status", "--porcelain", "-z"])
+                .map(|output| parse_porcelain(&output, &prefix, &root))
+                .unwrap_or_default();
+            // `git status` 可能会更新暂存区，在其之后记录修改时间
+            snapshot.index_mtime = modified_time(&git_dir.join("index"));
+            let _ = sender.send(snapshot);
+        });
+        self.running = Some(receiver);
+    }
+
+    /// 取回后台读取的结果，返回状态是否更新
+    pub fn poll(&mut self) -> bool {
+        let snapshot = match self.running.as_ref().map(Receiver::try_recv) {
+            Some(Ok(snapshot)) => snapshot,
+            Some(Err(TryRecvError::Disconnected)) => Snapshot::default(),
+            Some(Err(TryRecvError::Empty)) | None => return false,
+        };
+        self.snapshot = snapshot;
+        self.running = None;
+        if self.stale {
+            self.stale = false;
+            self.refresh();
+        }
+        true
+    }
+}
+
+// 解析 `git status --porcelain -z` 的输出，路径相对于仓库根目录，
+// 只保留工作区（仓库中的prefix目录）之下的文件，并将状态传递给其上级目录
+fn parse_porcelain(output: &str, prefix: &str, root: &Path) -> Snapshot {
+    let mut snapshot = Snapshot::default();
+    let mut fields = output.split('\0');
+    while let Some(field) = fields.next() {
+        let (code, path) = match (field.get(..2), field.get(3..)) {
+            (Some(code), Some(path)) => (code.as_bytes(), path),
+            _ => continue,
+        };
+        // 重命名与复制之后是原来的路径
+        if matches!(code[0], b'R' | b'C') {
+            fields.next();
+        }
+        let status = match (code[0], code[1]) {
+            (b'?', b'?') => GitStatus::Untracked,
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => GitStatus::Conflicted,
+            (b'A', _) => GitStatus::Added,
+            _ => GitStatus::Modified,
+        };
+        let path = match path.strip_prefix(prefix) {
+            Some(path) => root.join(path),
+            None => continue,
+        };
+        if status == GitStatus::Untracked && path.as_os_str().to_string_lossy().ends_with('/') {
+            snapshot.untracked_dirs.insert(path.clone());
+        }
+
+        for path in path.ancestors() {
+            let entry = snapshot
+                .statuses
+                .entry(path.to_path_buf())
+                .or_insert(status);
+            *entry = (*entry).max(status);
+            if path == root {
+                break;
+            }
+        }
+    }
+    snapshot
+}
+
+// 在dir中运行git，失败时返回None
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_porcelain, GitStatus, GitStatuses};
+
+    #[test]
+    fn statuses_propagate_to_directories() {
+        let root = Path::new("/repo/app");
+        let output = " M app/src/main.rs\0?? app/src/new.rs\0A  app/README\0\
+                      R  app/lib.rs\0app/old.rs\0UU app/src/merge.rs\0 M other/x.rs\0";
+        let statuses = parse_porcelain(output, "app/", root).statuses;
+
+        assert_eq!(
+            statuses.get(&root.join("src/main.rs")),
+            Some(&GitStatus::Modified)
+        );
+        assert_eq!(
+            statuses.get(&root.join("src/new.rs")),
+            Some(&GitStatus::Untracked)
+        );
+        assert_eq!(statuses.get(&root.join("README")), Some(&GitStatus::Added));
+        assert_eq!(
+            statuses.get(&root.join("lib.rs")),
+            Some(&GitStatus::Modified)
+        );
+        assert_eq!(statuses.get(&root.join("old.rs")), None);
+        assert_eq!(
+            statuses.get(&root.join("src")),
+            Some(&GitStatus::Conflicted)
+        );
+        assert_eq!(statuses.get(root), Some(&GitStatus::Conflicted));
+        assert_eq!(statuses.len(), 7);
+    }
+
+    #[test]
+    fn files_in_untracked_directories_are_untracked() {
+        let root = Path::new("/repo");
+        let mut statuses = GitStatuses {
+            root: root.to_path_buf(),
+            snapshot: parse_porcelain("?? new/\0 M src/main.rs\0", "", root),
+            ..Default::default()
+        };
+        assert_eq!(
+            statuses.get(&root.join("new/deep/a.rs")),
+            Some(GitStatus::Untracked)
+        );
+        assert_eq!(statuses.get(&root.join("new")), Some(GitStatus::Untracked));
+        assert_eq!(statuses.get(&root.join("src/lib.rs")), None);
+        assert!(!statuses.poll());
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::Path};
+
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{file_filter::FileFilter, location::Location};
+
+// 最多保留的匹配数，避免过于宽泛的模式占满内存
+const MAX_MATCHES: usize = 10000;
+// 超过该大小的文件不搜索
+const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+// 在文件开头的这些字节中出现NUL即视为二进制文件
+const BINARY_CHECK_BYTES: usize = 8192;
+
+/// 在root下所有未被忽略的文件中查找regex，跳过二进制与非UTF-8文件
+///
+/// 每行的每个匹配都是一个结果，按文件路径与位置排列。
+pub fn grep(root: &Path, ignore_patterns: &[String], regex: &Regex) -> Vec<Location> {
+    let mut locations = Vec::new();
+    for path in FileFilter::new(root, ignore_patterns).walk() {
+        if path
+            .metadata()
+            .map_or(true, |metadata| metadata.len() > MAX_FILE_SIZE)
+        {
+            continue;
+        }
+        let content = match fs::read(&path) {
+            Ok(bytes) if !bytes[..bytes.len().min(BINARY_CHECK_BYTES)].contains(&0) => {
+                match String::from_utf8(bytes) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        locations.extend(grep_content(&path, &content, regex));
+        if locations.len() >= MAX_MATCHES {
+            locations.truncate(MAX_MATCHES);
+            break;
+        }
+    }
+    locations
+}
+
+// 查找一个文件的内容
+fn grep_content(path: &Path, content: &str, regex: &Regex) -> Vec<Location> {
+    let mut locations = Vec::new();
+    for (line, text) in content.lines().enumerate() {
+        for found in regex.find_iter(text) {
+            // 空匹配（如 `^`）每行只记录一次
+            if found.as_str().is_empty() && found.start() > 0 {
+                continue;
+            }
+            locations.push(Location {
+                path: path.to_path_buf(),
+                line,
+                column: text[..found.start()].graphemes(true).count(),
+                text: text.to_string(),
+            });
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use regex::Regex;
+
+    use super::grep_content;
+
+    #[test]
+    fn every_match_is_located_by_grapheme_column() {
+        let regex = Regex::new(r"fo+").unwrap();
+        let content = "let foo = 1;\nnone\n// é foo fooo\n";
+        let found: Vec<(usize, usize)> = grep_content(Path::new("a.rs"), content, &regex)
+            .iter()
+            .map(|location| (location.line, location.column))
+            .collect();
+        assert_eq!(found, [(0, 4), (2, 5), (2, 9)]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use smallvec::SmallVec;
+
+use super::{InputMapper, PASTE_KEY};
+use crate::{application::Application, errors::*, modules::macros::Macros};
+
+pub const LEADER_KEY: &str = "<leader>";
+const SPACE_KEY: &str = "<space>";
+// 未绑定按键的默认处理
+pub const FALLBACK_KEY: &str = "_";
+
+pub type Handlers = SmallVec<[fn(&mut Application) -> Result<()>; 4]>;
+
+/// 按键序列绑定的动作
+#[derive(Clone)]
+pub enum KeyAction {
+    // 处理函数名与对应的函数，函数名用于展示绑定
+    Handlers(Vec<String>, Handlers),
+    // 回放寄存器中的宏
+    Macro(char),
+}
+
+impl KeyAction {
+    /// 由处理函数名（如 `cursor::move_down`）构造动作，`@a` 表示回放寄存器a中的宏
+    pub fn parse(
+        commands: &[&str],
+        handle_map: &HashMap<&str, fn(&mut Application) -> Result<()>>,
+    ) -> Result<KeyAction> {
+        if let [command] = commands {
+            if let Some(register) = command.strip_prefix('@') {
+                let register = Macros::register_of(register)
+                    .ok_or_else(|| format!("invalid macro register \"{command}\""))?;
+                return Ok(KeyAction::Macro(register));
+            }
+        }
+        if commands.is_empty() {
+            return Err("no command given".into());
+        }
+
+        let mut handlers = SmallVec::new();
+        for command in commands {
+            handlers.push(
+                *handle_map
+                    .get(command)
+                    .ok_or_else(|| format!("command \"{command}\" not found"))?,
+            );
+        }
+        Ok(KeyAction::Handlers(
+            commands.iter().map(|command| command.to_string()).collect(),
+            handlers,
+        ))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            KeyAction::Handlers(names, _) => names.join(" "),
+            KeyAction::Macro(register) => format!("@{register}"),
+        }
+    }
+}
+
+/// 按键序列前缀树
+///
+/// 中间节点同样可以绑定动作，例如同时绑定 `g` 与 `g g` 时，
+/// 输入 `g` 后等待后续按键，超时或后续按键无法匹配时执行 `g` 的动作。
+#[derive(Clone, Default)]
+pub struct KeyTrie {
+    action: Option<KeyAction>,
+    children: HashMap<String, KeyTrie>,
+}
+
+impl KeyTrie {
+    pub fn insert(&mut self, keys: &[String], action: KeyAction) {
+        let mut node = self;
+        for key in keys {
+            node = node.children.entry(key.clone()).or_default();
+        }
+        node.action = Some(action);
+    }
+
+    /// 删除keys上绑定的动作，返回是否存在该绑定
+    pub fn remove(&mut self, keys: &[String]) -> bool {
+        match keys.split_first() {
+            None => self.action.take().is_some(),
+            Some((key, rest)) => {
+                let removed = match self.children.get_mut(key) {
+                    Some(child) => child.remove(rest),
+                    None => return false,
+                };
+                // 清理不再有任何绑定的分支
+                if self.children[key].action.is_none() && self.children[key].children.is_empty() {
+                    self.children.remove(key);
+                }
+                removed
+            }
+        }
+    }
+
+    /// 所有绑定，按按键序列排序
+    pub fn bindings(&self) -> Vec<(Vec<String>, &KeyAction)> {
+        let mut bindings = Vec::new();
+        self.collect_bindings(&mut Vec::new(), &mut bindings);
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    fn collect_bindings<'a>(
+        &'a self,
+        prefix: &mut Vec<String>,
+        bindings: &mut Vec<(Vec<String>, &'a KeyAction)>,
+    ) {
+        if let Some(action) = &self.action {
+            bindings.push((prefix.clone(), action));
+        }
+        for (key, child) in self.children.iter() {
+            prefix.push(key.clone());
+            child.collect_bindings(prefix, bindings);
+            prefix.pop();
+        }
+    }
+
+    pub fn get(&self, keys: &[String]) -> Option<&KeyTrie> {
+        let mut node = self;
+        for key in keys {
+            node = node.children.get(key)?;
+        }
+        Some(node)
+    }
+
+    pub fn action(&self) -> Option<&KeyAction> {
+        self.action.as_ref()
+    }
+
+    /// 是否还有更长的序列以此为前缀
+    pub fn is_prefix(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// 匹配keys中已绑定的最长前缀，返回前缀长度与对应动作
+    pub fn longest_match(&self, keys: &[String]) -> Option<(usize, &KeyAction)> {
+        (1..=keys.len())
+            .rev()
+            .find_map(|len| Some((len, self.get(&keys[..len])?.action()?)))
+    }
+}
+
+/// 将配置中的按键序列拆分为按键名，按键之间以空格分隔，如 `g g`、`<leader> f`
+pub fn parse_sequence(sequence: &str, leader: &str) -> Vec<String> {
+    // 单独的空格键
+    if sequence == " " {
+        return vec![sequence.to_string()];
+    }
+
+    sequence
+        .split_whitespace()
+        .map(|key| match key {
+            LEADER_KEY => normalize_key(leader),
+            key => normalize_key(key),
+        })
+        .collect()
+}
+
+/// 解析命令行中的按键序列，按键之间不分隔，具名按键写在尖括号中，
+/// 如 `gg`、`<leader>w`、`<ctrl-w>j`
+pub fn parse_notation(notation: &str, leader: &str) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut rest = notation;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>').filter(|end| *end > 1) {
+                let name = &rest[1..end];
+                keys.push(match name {
+                    "leader" => normalize_key(leader),
+                    "space" => " ".to_string(),
+                    name => name.to_string(),
+                });
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        // 大写字母由shift组合输入
+        keys.push(match c {
+            'A'..='Z' => format!("shift-{c}"),
+            c => c.to_string(),
+        });
+        rest = &rest[c.len_utf8()..];
+    }
+
+    check_keys(&keys)?;
+    Ok(keys)
+}
+
+/// 检查序列中的按键名都能够被输入
+pub fn check_keys(keys: &[String]) -> Result<()> {
+    if keys.is_empty() {
+        return Err("empty key sequence".into());
+    }
+    if keys.len() > 1 && keys.iter().any(|key| key == FALLBACK_KEY) {
+        return Err(format!("\"{FALLBACK_KEY}\" can't be part of a key sequence").into());
+    }
+    for key in keys {
+        if InputMapper::is_mouse_key(key) || key == PASTE_KEY {
+            // 鼠标与粘贴事件不会等待后续按键
+            if keys.len() > 1 {
+                return Err(format!("\"{key}\" can't be part of a key sequence").into());
+            }
+        } else if key != FALLBACK_KEY && key != " " && InputMapper::str_map_key_event(key).is_none()
+        {
+            return Err(format!("unknown key \"{key}\"").into());
+        }
+    }
+    Ok(())
+}
+
+/// 按配置文件的写法展示按键序列
+pub fn display_keys(keys: &[String]) -> String {
+    keys.iter()
+        .map(|key| match key.as_str() {
+            " " => SPACE_KEY,
+            key => key,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_key(key: &str) -> String {
+    match key {
+        SPACE_KEY => " ".to_string(),
+        key => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::SmallVec;
+
+    use super::{parse_notation, parse_sequence, KeyAction, KeyTrie};
+
+    #[test]
+    fn leader_is_expanded() {
+        assert_eq!(parse_sequence("<leader> f", "<space>"), vec![" ", "f"]);
+        assert_eq!(parse_sequence("g g", "\\"), vec!["g", "g"]);
+        assert_eq!(parse_sequence(" ", "\\"), vec![" "]);
+    }
+
+    #[test]
+    fn longest_bound_prefix_wins() {
+        let keys = |keys: &str| parse_sequence(keys, "\\");
+        let mut trie = KeyTrie::default();
+        trie.insert(&keys("g"), KeyAction::Macro('a'));
+        trie.insert(&keys("g g x"), KeyAction::Handlers(vec![], SmallVec::new()));
+
+        assert!(trie.get(&keys("g g")).unwrap().is_prefix());
+        assert!(trie.get(&keys("g g")).unwrap().action().is_none());
+        assert!(matches!(
+            trie.longest_match(&keys("g g y")),
+            Some((1, KeyAction::Macro('a')))
+        ));
+        assert!(trie.longest_match(&keys("x")).is_none());
+    }
+
+    #[test]
+    fn notation_matches_config_syntax() {
+        assert_eq!(
+            parse_notation("<leader>gA", "<space>").unwrap(),
+            parse_sequence("<leader> g shift-A", "<space>")
+        );
+        assert_eq!(
+            parse_notation("<ctrl-w>j", "\\").unwrap(),
+            vec!["ctrl-w", "j"]
+        );
+        assert!(parse_notation("<bogus>", "\\").is_err());
+    }
+
+    #[test]
+    fn removing_prunes_empty_branches() {
+        let keys = |keys: &str| parse_sequence(keys, "\\");
+        let mut trie = KeyTrie::default();
+        trie.insert(&keys("g g"), KeyAction::Macro('a'));
+
+        assert!(!trie.remove(&keys("g")));
+        assert!(trie.remove(&keys("g g")));
+        assert!(trie.get(&keys("g")).is_none());
+    }
+}
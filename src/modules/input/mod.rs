@@ -1,48 +1,115 @@
-use std::{collections::HashMap, ffi::OsStr, fs::read_to_string, path::PathBuf};
+use std::{collections::HashMap, ffi::OsStr, fs::read_to_string, path::PathBuf, time::Duration};
 
-use crate::{
-    application::{mode::ModeKey, Application},
-    errors::*,
+use crate::{application::mode::ModeKey, errors::*};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
-use crossterm::event::{Event, KeyEvent, KeyEventKind, KeyModifiers};
+use keymap::KeyTrie;
 use linked_hash_map::LinkedHashMap;
 
-use smallvec::SmallVec;
 use strum::IntoEnumIterator;
 use yaml_rust::{Yaml, YamlLoader};
 
+pub mod keymap;
+
 const INPUT_CONFIG_NAME: &str = "input.yaml";
+const LEADER_CONFIG_KEY: &str = "leader";
+const TIMEOUT_CONFIG_KEY: &str = "timeout";
+const COUNT_MODES_CONFIG_KEY: &str = "count_modes";
+const DEFAULT_LEADER: &str = "\\";
+const DEFAULT_TIMEOUT: u64 = 1000;
+// 终端粘贴事件对应的按键名，粘贴的文本见 `Monitor::last_paste`
+pub const PASTE_KEY: &str = "paste";
+
+/// 输入配置：各模式的按键前缀树，以及多键序列的等待时长
+pub struct InputConfig {
+    pub keymaps: HashMap<String, KeyTrie>,
+    pub leader: String,
+    // 接受数字计数前缀的模式
+    pub count_modes: Vec<String>,
+    pub timeout: Duration,
+    // 配置中无法解析而被跳过的部分，启动后作为警告显示
+    pub errors: Vec<String>,
+}
+
 pub struct InputLoader;
 
 impl InputLoader {
-    pub fn load(
-        path: PathBuf,
-    ) -> Result<HashMap<String, HashMap<String, SmallVec<[fn(&mut Application) -> Result<()>; 4]>>>>
-    {
+    pub fn load(path: PathBuf) -> Result<InputConfig> {
+        let mut errors = Vec::new();
+        // 用户配置无法读取时只使用默认配置
         #[cfg(not(feature = "dragonos"))]
-        let data = Self::load_user(path)?;
+        let data = Self::load_user(path).unwrap_or_else(|e| {
+            errors.push(e.to_string());
+            None
+        });
         #[cfg(feature = "dragonos")]
         let data = None;
         let default = Self::load_default()?;
-        let handle_map = Self::generate_handle_map(
-            data,
-            default
-                .as_hash()
-                .ok_or_else(|| "default input config didn't return a hash of key bindings")?,
-        )?;
-        Ok(handle_map)
+        let default = default
+            .as_hash()
+            .ok_or_else(|| "default input config didn't return a hash of key bindings")?;
+
+        // 用户配置优先
+        let option = |key: &str| {
+            let key = Yaml::String(key.to_string());
+            data.as_ref()
+                .and_then(|data| data.get(&key))
+                .or_else(|| default.get(&key))
+                .cloned()
+        };
+        let leader = match option(LEADER_CONFIG_KEY) {
+            Some(Yaml::String(leader)) => leader,
+            _ => DEFAULT_LEADER.to_string(),
+        };
+        let timeout = match option(TIMEOUT_CONFIG_KEY) {
+            Some(Yaml::Integer(millis)) if millis > 0 => millis as u64,
+            _ => DEFAULT_TIMEOUT,
+        };
+
+        let count_modes = match option(COUNT_MODES_CONFIG_KEY) {
+            Some(Yaml::Array(modes)) => modes
+                .iter()
+                .filter_map(|mode| mode.as_str().map(|mode| mode.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let keymaps = Self::generate_handle_map(data, default, &leader, &mut errors);
+        Ok(InputConfig {
+            keymaps,
+            leader,
+            count_modes,
+            timeout: Duration::from_millis(timeout),
+            errors,
+        })
     }
 
+    /// 跳过无法解析的绑定并记录在errors中，其余绑定照常加载
     fn generate_handle_map(
         extra_data: Option<LinkedHashMap<Yaml, Yaml>>,
         default: &LinkedHashMap<Yaml, Yaml>,
-    ) -> Result<HashMap<String, HashMap<String, SmallVec<[fn(&mut Application) -> Result<()>; 4]>>>>
-    {
-        let mut handle_map = HashMap::new();
+        leader: &str,
+        errors: &mut Vec<String>,
+    ) -> HashMap<String, KeyTrie> {
+        let mut keymaps = HashMap::new();
+        let mut mode_errors = Vec::new();
         for mode_key in ModeKey::iter() {
-            mode_key.generate_handle_map(&mut handle_map, extra_data.as_ref(), default)?;
+            mode_key.generate_handle_map(
+                &mut keymaps,
+                extra_data.as_ref(),
+                default,
+                leader,
+                &mut mode_errors,
+            );
         }
-        Ok(handle_map)
+
+        errors.extend(
+            mode_errors
+                .into_iter()
+                .map(|e| format!("{}: {}", INPUT_CONFIG_NAME, e)),
+        );
+        keymaps
     }
 
     fn load_user(path: PathBuf) -> Result<Option<LinkedHashMap<Yaml, Yaml>>> {
@@ -89,26 +156,154 @@ impl InputMapper {
             Event::FocusGained => None,
             Event::FocusLost => None,
             Event::Key(key_event) => {
-                return Some(Self::key_event_map_str(key_event));
+                return Some(Self::key_event_record_str(key_event));
             }
-            Event::Mouse(_) => None,
-            Event::Paste(_) => None,
+            Event::Mouse(mouse_event) => Self::mouse_event_map_str(mouse_event),
+            Event::Paste(_) => Some(PASTE_KEY.to_string()),
             Event::Resize(_, _) => None,
         }
     }
 
-    fn key_event_map_str(event: KeyEvent) -> String {
-        if let KeyEventKind::Press = event.kind {
-            let mut modifier = String::new();
-            if event.modifiers.contains(KeyModifiers::CONTROL) {
-                modifier.push_str("ctrl-");
+    /// 按键对应的字符串，可以由 `str_map_key_event` 还原
+    pub fn key_event_record_str(event: KeyEvent) -> String {
+        Self::key_name(event)
+    }
+
+    /// 宏与 `.` 记录的事件字符串，可以由 `str_map_event` 还原
+    ///
+    /// 粘贴事件记录为 `paste:` 之后跟粘贴的文本，其他事件不记录。
+    pub fn event_record_str(event: &Event) -> Option<String> {
+        match event {
+            Event::Key(key_event) => Some(Self::key_event_record_str(*key_event)),
+            Event::Paste(text) => Some(format!("{}:{}", PASTE_KEY, text)),
+            _ => None,
+        }
+    }
+
+    /// 将记录的事件字符串还原为事件，用于回放宏与 `.`
+    pub fn str_map_event(key: &str) -> Option<Event> {
+        match key
+            .strip_prefix(PASTE_KEY)
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            Some(text) => Some(Event::Paste(text.to_string())),
+            None => Self::str_map_key_event(key).map(Event::Key),
+        }
+    }
+
+    /// 鼠标事件对应的按键名，如 `mouse-down-left`、`scroll-up`，鼠标移动不产生按键
+    pub fn mouse_event_map_str(event: MouseEvent) -> Option<String> {
+        let button = |button: MouseButton| match button {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+        };
+        let name = match event.kind {
+            MouseEventKind::Down(b) => format!("mouse-down-{}", button(b)),
+            MouseEventKind::Up(b) => format!("mouse-up-{}", button(b)),
+            MouseEventKind::Drag(b) => format!("mouse-drag-{}", button(b)),
+            MouseEventKind::ScrollUp => "scroll-up".to_string(),
+            MouseEventKind::ScrollDown => "scroll-down".to_string(),
+            MouseEventKind::ScrollLeft => "scroll-left".to_string(),
+            MouseEventKind::ScrollRight => "scroll-right".to_string(),
+            MouseEventKind::Moved => return None,
+        };
+        Some(format!(
+            "{}{}",
+            Self::modifier_prefix(event.modifiers),
+            name
+        ))
+    }
+
+    pub fn is_mouse_key(key: &str) -> bool {
+        let key = ["ctrl-", "alt-", "shift-"]
+            .iter()
+            .fold(key, |key, prefix| key.strip_prefix(prefix).unwrap_or(key));
+        match key.strip_prefix("mouse-") {
+            Some(rest) => {
+                let mut parts = rest.splitn(2, '-');
+                matches!(parts.next(), Some("down" | "up" | "drag"))
+                    && matches!(parts.next(), Some("left" | "right" | "middle"))
             }
-            if event.modifiers.contains(KeyModifiers::ALT) {
-                modifier.push_str("alt-");
+            None => matches!(
+                key,
+                "scroll-up" | "scroll-down" | "scroll-left" | "scroll-right"
+            ),
+        }
+    }
+
+    /// 将按键字符串还原为按键事件，用于回放宏
+    pub fn str_map_key_event(key: &str) -> Option<KeyEvent> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = key;
+        loop {
+            // 形如 "ctrl--" 的按键本身就是 '-'
+            if rest.len() > 1 {
+                if let Some(r) = rest.strip_prefix("ctrl-") {
+                    modifiers |= KeyModifiers::CONTROL;
+                    rest = r;
+                    continue;
+                }
+                if let Some(r) = rest.strip_prefix("alt-") {
+                    modifiers |= KeyModifiers::ALT;
+                    rest = r;
+                    continue;
+                }
+                if let Some(r) = rest.strip_prefix("shift-") {
+                    modifiers |= KeyModifiers::SHIFT;
+                    rest = r;
+                    continue;
+                }
             }
-            if event.modifiers.contains(KeyModifiers::SHIFT) {
-                modifier.push_str("shift-");
+            break;
+        }
+
+        let code = match rest {
+            "backspace" => KeyCode::Backspace,
+            "enter" => KeyCode::Enter,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "escape" => KeyCode::Esc,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('f'), Some(_)) => KeyCode::F(rest[1..].parse().ok()?),
+                    _ => return None,
+                }
             }
+        };
+
+        Some(KeyEvent::new(code, modifiers))
+    }
+
+    fn modifier_prefix(modifiers: KeyModifiers) -> String {
+        let mut modifier = String::new();
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            modifier.push_str("ctrl-");
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            modifier.push_str("alt-");
+        }
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            modifier.push_str("shift-");
+        }
+        modifier
+    }
+
+    fn key_name(event: KeyEvent) -> String {
+        if let KeyEventKind::Press = event.kind {
+            let modifier = Self::modifier_prefix(event.modifiers);
             let keycode_str = match event.code {
                 crossterm::event::KeyCode::Backspace => "backspace".into(),
                 crossterm::event::KeyCode::Enter => "enter".into(),
@@ -125,13 +320,7 @@ impl InputMapper {
                 crossterm::event::KeyCode::Delete => "delete".into(),
                 crossterm::event::KeyCode::Insert => "insert".into(),
                 crossterm::event::KeyCode::F(f) => format!("f{f}"),
-                crossterm::event::KeyCode::Char(c) => {
-                    if c.is_digit(10) {
-                        "num".to_string()
-                    } else {
-                        c.into()
-                    }
-                }
+                crossterm::event::KeyCode::Char(c) => c.into(),
                 crossterm::event::KeyCode::Null => "".into(),
                 crossterm::event::KeyCode::Esc => "escape".into(),
                 crossterm::event::KeyCode::CapsLock => "caps_lock".into(),
@@ -166,3 +355,24 @@ impl InputMapper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use super::InputMapper;
+
+    #[test]
+    fn recorded_events_round_trip() {
+        let events = [
+            Event::Key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)),
+            Event::Paste("paste: a\nb".to_string()),
+            Event::Paste(String::new()),
+        ];
+        for event in events {
+            let key = InputMapper::event_record_str(&event).unwrap();
+            assert_eq!(InputMapper::str_map_event(&key), Some(event));
+        }
+        assert_eq!(InputMapper::str_map_event("paste"), None);
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::{Path, PathBuf};
+
+/// 文件中的一个位置及该处的说明，如grep匹配到的行
+///
+/// line与column从0开始，column按字素簇计数，与光标的offset一致；
+/// 显示时转换为从1开始的行号与列号。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+impl Location {
+    /// `file:line:column: text` 形式的描述，root下的文件显示相对路径
+    pub fn describe(&self, root: &Path) -> String {
+        let path = self.path.strip_prefix(root).unwrap_or(&self.path);
+        format!(
+            "{}:{}:{}: {}",
+            path.to_string_lossy(),
+            self.line + 1,
+            self.column + 1,
+            self.text.trim()
+        )
+    }
+}
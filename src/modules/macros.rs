@@ -0,0 +1,195 @@
+use std::{
+    collections::BTreeMap,
+    fs::{read_to_string, write},
+    path::PathBuf,
+};
+
+use linked_hash_map::LinkedHashMap;
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::errors::*;
+
+const MACRO_CONFIG_NAME: &str = "macros.yaml";
+
+/// `q`/`@` 之后等待输入寄存器名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroPending {
+    Record,
+    Play,
+}
+
+/// 宏寄存器
+///
+/// 宏以 `InputMapper::event_record_str` 得到的按键字符串序列保存，
+/// 持久化在输入配置目录下的 `macros.yaml` 中，可以直接编辑：
+/// ```yaml
+/// a: [j, shift-A, ";", "paste:text", escape]
+/// ```
+#[derive(Debug, Default)]
+pub struct Macros {
+    path: Option<PathBuf>,
+    registers: BTreeMap<char, Vec<String>>,
+    recording: Option<(char, Vec<String>)>,
+    pub pending: Option<MacroPending>,
+    pub last_played: Option<char>,
+    // 正在回放的嵌套层数，回放产生的按键不会被录制
+    pub depth: usize,
+}
+
+impl Macros {
+    pub fn load(input_config_path: PathBuf) -> Result<Macros> {
+        let mut macros = Macros::default();
+        #[cfg(feature = "dragonos")]
+        let _ = input_config_path;
+        #[cfg(not(feature = "dragonos"))]
+        {
+            let path = input_config_path.join(MACRO_CONFIG_NAME);
+            if path.is_file() {
+                let yaml = YamlLoader::load_from_str(&read_to_string(&path)?)
+                    .chain_err(|| format!("Couldn't parse macro file: {:?}", path))?
+                    .into_iter()
+                    .next()
+                    .unwrap_or(Yaml::Null);
+                macros.registers = Self::parse(&yaml)
+                    .chain_err(|| format!("Couldn't parse macro file: {:?}", path))?;
+            }
+            macros.path = Some(path);
+        }
+
+        Ok(macros)
+    }
+
+    fn parse(yaml: &Yaml) -> Result<BTreeMap<char, Vec<String>>> {
+        let mut registers = BTreeMap::new();
+        let hash = match yaml {
+            Yaml::Null => return Ok(registers),
+            Yaml::Hash(hash) => hash,
+            _ => return Err("macro file didn't return a hash of registers".into()),
+        };
+
+        for (register, keys) in hash {
+            let register = register
+                .as_str()
+                .and_then(Self::register_of)
+                .ok_or_else(|| format!("invalid macro register {:?}", register))?;
+            let keys = keys
+                .as_vec()
+                .ok_or_else(|| format!("macro \"{}\" isn't a list of keys", register))?
+                .iter()
+                .map(|key| match key {
+                    Yaml::String(key) => Ok(key.clone()),
+                    // 未加引号的数字键
+                    Yaml::Integer(digit) => Ok(digit.to_string()),
+                    _ => Err(format!(
+                        "macro \"{}\" has an invalid key {:?}",
+                        register, key
+                    )),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            registers.insert(register, keys);
+        }
+
+        Ok(registers)
+    }
+
+    /// 寄存器名为单个字母或数字，大写字母表示追加到对应的小写寄存器
+    pub fn register_of(name: &str) -> Option<char> {
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphanumeric() => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, register: char) -> Option<&Vec<String>> {
+        self.registers.get(&register.to_ascii_lowercase())
+    }
+
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    pub fn start_recording(&mut self, register: char) {
+        let keys = if register.is_ascii_uppercase() {
+            self.get(register).cloned().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.recording = Some((register.to_ascii_lowercase(), keys));
+    }
+
+    /// 结束录制并写回文件，`drop_last` 用于丢弃触发结束的按键本身
+    pub fn stop_recording(&mut self, drop_last: bool) -> Result<()> {
+        if let Some((register, mut keys)) = self.recording.take() {
+            if drop_last {
+                keys.pop();
+            }
+            self.registers.insert(register, keys);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, key: String) {
+        if self.depth > 0 || key.is_empty() {
+            return;
+        }
+        if let Some((_, keys)) = self.recording.as_mut() {
+            keys.push(key);
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut hash = LinkedHashMap::new();
+        for (register, keys) in self.registers.iter() {
+            hash.insert(
+                Yaml::String(register.to_string()),
+                Yaml::Array(keys.iter().map(|key| Yaml::String(key.clone())).collect()),
+            );
+        }
+
+        let mut out = String::new();
+        YamlEmitter::new(&mut out)
+            .dump(&Yaml::Hash(hash))
+            .chain_err(|| "Couldn't serialize macros")?;
+        out.push('\n');
+        write(path, out).chain_err(|| format!("Couldn't write macro file: {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::YamlLoader;
+
+    use super::Macros;
+
+    #[test]
+    fn uppercase_register_appends() {
+        let mut macros = Macros::default();
+        macros.start_recording('a');
+        macros.record("j".to_string());
+        macros.record("q".to_string());
+        macros.stop_recording(true).unwrap();
+
+        macros.start_recording('A');
+        macros.record("x".to_string());
+        macros.stop_recording(false).unwrap();
+
+        assert_eq!(macros.get('a').unwrap(), &vec!["j", "x"]);
+    }
+
+    #[test]
+    fn parse_macro_file() {
+        let yaml = YamlLoader::load_from_str("a: [j, 5, shift-A]\n").unwrap();
+        let registers = Macros::parse(&yaml[0]).unwrap();
+        assert_eq!(registers[&'a'], vec!["j", "5", "shift-A"]);
+
+        let yaml = YamlLoader::load_from_str("ab: [j]\n").unwrap();
+        assert!(Macros::parse(&yaml[0]).is_err());
+    }
+}
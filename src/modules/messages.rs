@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+
+use held_core::view::colors::Colors;
+
+use crate::errors::*;
+
+// 保留的历史消息条数
+const MAX_HISTORY: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub level: MessageLevel,
+    pub content: String,
+}
+
+impl Message {
+    /// 错误链上的每一层原因依次以 `: ` 连接
+    pub fn from_error(error: &Error) -> Message {
+        Message {
+            level: MessageLevel::Error,
+            content: error
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(": "),
+        }
+    }
+
+    pub fn colors(&self) -> Colors {
+        match self.level {
+            MessageLevel::Info => Colors::Focused,
+            MessageLevel::Warning | MessageLevel::Error => Colors::Warning,
+        }
+    }
+}
+
+/// 等待显示的消息队列与历史消息
+///
+/// 队首的消息显示在状态栏上，按键后被关闭并显示下一条；
+/// 所有消息同时记录在历史中，可以通过 `:messages` 查看。
+#[derive(Debug, Default)]
+pub struct Messages {
+    queue: VecDeque<Message>,
+    history: VecDeque<Message>,
+}
+
+impl Messages {
+    pub fn info<T: Into<String>>(&mut self, content: T) {
+        self.push(MessageLevel::Info, content.into());
+    }
+
+    pub fn warning<T: Into<String>>(&mut self, content: T) {
+        self.push(MessageLevel::Warning, content.into());
+    }
+
+    pub fn error(&mut self, error: &Error) {
+        self.push_message(Message::from_error(error));
+    }
+
+    fn push(&mut self, level: MessageLevel, content: String) {
+        self.push_message(Message { level, content });
+    }
+
+    fn push_message(&mut self, message: Message) {
+        if self.history.len() == MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(message.clone());
+        self.queue.push_back(message);
+    }
+
+    /// 当前显示的消息
+    pub fn current(&self) -> Option<&Message> {
+        self.queue.front()
+    }
+
+    /// 关闭当前显示的消息
+    pub fn dismiss(&mut self) {
+        self.queue.pop_front();
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &Message> {
+        self.history.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageLevel, Messages};
+    use crate::errors::*;
+
+    #[test]
+    fn messages_are_shown_in_order_and_kept_in_history() {
+        let mut messages = Messages::default();
+        messages.info("written");
+        let error: Result<()> = Err(Error::from("No such file")).chain_err(|| "Couldn't save");
+        messages.error(&error.unwrap_err());
+
+        assert_eq!(messages.current().unwrap().content, "written");
+        messages.dismiss();
+        let current = messages.current().unwrap();
+        assert_eq!(current.level, MessageLevel::Error);
+        assert_eq!(current.content, "Couldn't save: No such file");
+        messages.dismiss();
+        assert!(messages.current().is_none());
+        assert_eq!(messages.history().count(), 2);
+    }
+}
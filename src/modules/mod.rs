@@ -1,7 +1,17 @@
 use app_dirs2::AppInfo;
 
+pub mod cmdline;
+pub mod file_filter;
+pub mod file_tree;
+pub mod git_status;
+pub mod grep;
 pub mod input;
+pub mod location;
+pub mod macros;
+pub mod messages;
 pub mod perferences;
+pub mod quickfix;
+pub mod repeat;
 const APP_INFO: AppInfo = AppInfo {
     name: "held",
     author: "DragonOS Community",
@@ -4,8 +4,11 @@ use std::{
     cell::RefCell,
     path::{Path, PathBuf},
     rc::Rc,
+    time::Duration,
 };
 use yaml::YamlPerferences;
+
+use crate::view::status_line::StatusLineLayout;
 use yaml_rust::YamlLoader;
 
 use super::APP_INFO;
@@ -16,12 +19,37 @@ const SYNTAX_PATH: &str = "syntaxes";
 const THEME_PATH: &str = "themes";
 const INPUT_CONFIG_PATH: &str = "input";
 const PLUGINS_PATH: &str = "plugins";
+const HISTORY_PATH: &str = "history";
 const THEME_KET: &str = "theme";
 const LANGUAGE_KEY: &str = "language";
 const LANGUAGE_SYNTAX_KEY: &str = "syntax";
 const LINE_WRAPPING_KEY: &str = "line_wrapping";
 const SOFT_TAB_KEY: &str = "soft_tab";
 const TAB_WIDTH_KEY: &str = "tab_width";
+const STATUS_LINE_KEY: &str = "status_line";
+const UNDO_BREAK_KEY: &str = "undo_break";
+const UNDO_BREAK_NEWLINE_KEY: &str = "newline";
+const UNDO_BREAK_PAUSE_KEY: &str = "pause";
+const IGNORE_KEY: &str = "ignore";
+const MAKE_KEY: &str = "make";
+const TREE_KEY: &str = "tree";
+const TREE_WIDTH_KEY: &str = "width";
+const TREE_HIDDEN_KEY: &str = "hidden";
+const TREE_ICONS_KEY: &str = "icons";
+
+/// 可以通过 `:set` 修改的设置项，嵌套的设置项以 `.` 分隔层级
+pub const PERFERENCE_KEYS: &[&str] = &[
+    THEME_KET,
+    LINE_WRAPPING_KEY,
+    SOFT_TAB_KEY,
+    TAB_WIDTH_KEY,
+    "undo_break.newline",
+    "undo_break.pause",
+    MAKE_KEY,
+    "tree.width",
+    "tree.hidden",
+    "tree.icons",
+];
 
 pub trait Perferences {
     /// 载入
@@ -69,11 +97,52 @@ pub trait Perferences {
         Ok(PathBuf::new())
     }
 
+    // 输入历史保存路径
+    fn history_path(&self) -> Result<PathBuf> {
+        #[cfg(not(feature = "dragonos"))]
+        {
+            app_dir(AppDataType::UserData, &APP_INFO, HISTORY_PATH)
+                .chain_err(|| "Couldn't create a history directory or build a path to it")
+        }
+        #[cfg(feature = "dragonos")]
+        Ok(PathBuf::new())
+    }
+
     // 设置的主题名字
     fn theme_name(&self) -> Option<String>;
 
     // 返回设置的语法定义：例：test.rs -> rs test.cpp -> cpp
     fn syntax_definition_name(&self, path: &Path) -> Option<String>;
+
+    // 状态栏左中右区域的片段排列
+    fn status_line_layout(&self) -> StatusLineLayout;
+
+    // 插入模式下换行时是否拆分撤销单元
+    fn undo_break_on_newline(&self) -> bool;
+
+    // 插入模式下停顿超过该时长后拆分撤销单元，None表示不拆分
+    fn undo_break_pause(&self) -> Option<Duration>;
+
+    // 查找文件与目录树中忽略的路径，写法与 `.gitignore` 相同
+    fn ignore_patterns(&self) -> Vec<String>;
+
+    // `:make` 运行的构建命令
+    fn make_command(&self) -> String;
+
+    // 目录树面板的宽度
+    fn tree_width(&self) -> usize;
+
+    // 目录树是否显示以 `.` 开头的文件
+    fn tree_show_hidden(&self) -> bool;
+
+    // 目录树是否使用Nerd Font图标
+    fn tree_icons(&self) -> bool;
+
+    // 设置项当前的值，key为 `PERFERENCE_KEYS` 中的一项
+    fn get(&self, key: &str) -> Option<String>;
+
+    // 运行时修改设置项，不会写回配置文件
+    fn set(&mut self, key: &str, value: &str) -> Result<()>;
 }
 
 pub struct PerferencesManager;
@@ -142,4 +211,44 @@ impl Perferences for DummyPerferences {
     fn input_config_path(&self) -> Result<PathBuf> {
         todo!()
     }
+
+    fn status_line_layout(&self) -> StatusLineLayout {
+        StatusLineLayout::default()
+    }
+
+    fn undo_break_on_newline(&self) -> bool {
+        false
+    }
+
+    fn undo_break_pause(&self) -> Option<Duration> {
+        None
+    }
+
+    fn ignore_patterns(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn make_command(&self) -> String {
+        "make".to_string()
+    }
+
+    fn tree_width(&self) -> usize {
+        30
+    }
+
+    fn tree_show_hidden(&self) -> bool {
+        false
+    }
+
+    fn tree_icons(&self) -> bool {
+        false
+    }
+
+    fn get(&self, _key: &str) -> Option<String> {
+        None
+    }
+
+    fn set(&mut self, _key: &str, _value: &str) -> Result<()> {
+        Ok(())
+    }
 }
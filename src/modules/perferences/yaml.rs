@@ -1,6 +1,15 @@
-use super::{Perferences, LINE_WRAPPING_KEY, SOFT_TAB_KEY, TAB_WIDTH_KEY, THEME_KET};
+use super::{
+    Perferences, IGNORE_KEY, LINE_WRAPPING_KEY, MAKE_KEY, PERFERENCE_KEYS, SOFT_TAB_KEY,
+    STATUS_LINE_KEY, TAB_WIDTH_KEY, THEME_KET, TREE_HIDDEN_KEY, TREE_ICONS_KEY, TREE_KEY,
+    TREE_WIDTH_KEY, UNDO_BREAK_KEY, UNDO_BREAK_NEWLINE_KEY, UNDO_BREAK_PAUSE_KEY,
+};
+use crate::errors::*;
 use crate::modules::perferences::{LANGUAGE_KEY, LANGUAGE_SYNTAX_KEY};
-use yaml_rust::Yaml;
+use crate::view::status_line::StatusLineLayout;
+use error_chain::bail;
+use std::mem;
+use std::time::Duration;
+use yaml_rust::{Yaml, YamlLoader};
 
 pub struct YamlPerferences {
     data: Yaml,
@@ -10,6 +19,23 @@ impl YamlPerferences {
     pub fn new(yaml: Yaml) -> YamlPerferences {
         YamlPerferences { data: yaml }
     }
+
+    // 取得以 `.` 分隔的key对应的节点，不存在时创建
+    fn node_mut(&mut self, key: &str) -> Option<&mut Yaml> {
+        let mut node = &mut self.data;
+        for key in key.split('.') {
+            if let Yaml::Null | Yaml::BadValue = node {
+                *node = Yaml::Hash(Default::default());
+            }
+            node = match node {
+                Yaml::Hash(hash) => hash
+                    .entry(Yaml::String(key.to_string()))
+                    .or_insert(Yaml::Null),
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
 }
 
 impl Perferences for YamlPerferences {
@@ -44,4 +70,125 @@ impl Perferences for YamlPerferences {
 
         None
     }
+
+    fn status_line_layout(&self) -> StatusLineLayout {
+        let mut layout = StatusLineLayout::default();
+        let status_line = &self.data[STATUS_LINE_KEY];
+        for (key, area) in [
+            ("left", &mut layout.left),
+            ("center", &mut layout.center),
+            ("right", &mut layout.right),
+        ] {
+            // 未配置的区域保持默认排列
+            if let Some(names) = status_line[key].as_vec() {
+                *area = names
+                    .iter()
+                    .filter_map(|name| name.as_str().map(|name| name.to_owned()))
+                    .collect();
+            }
+        }
+        layout
+    }
+
+    fn undo_break_on_newline(&self) -> bool {
+        self.data[UNDO_BREAK_KEY][UNDO_BREAK_NEWLINE_KEY]
+            .as_bool()
+            .unwrap_or(false)
+    }
+
+    fn undo_break_pause(&self) -> Option<Duration> {
+        // 单位为毫秒，0表示不按停顿拆分
+        match self.data[UNDO_BREAK_KEY][UNDO_BREAK_PAUSE_KEY].as_i64() {
+            Some(millis) if millis > 0 => Some(Duration::from_millis(millis as u64)),
+            _ => None,
+        }
+    }
+
+    fn ignore_patterns(&self) -> Vec<String> {
+        self.data[IGNORE_KEY]
+            .as_vec()
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|pattern| pattern.as_str().map(|pattern| pattern.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn make_command(&self) -> String {
+        self.data[MAKE_KEY].as_str().unwrap_or("make").to_owned()
+    }
+
+    fn tree_width(&self) -> usize {
+        self.data[TREE_KEY][TREE_WIDTH_KEY].as_i64().unwrap_or(30) as usize
+    }
+
+    fn tree_show_hidden(&self) -> bool {
+        self.data[TREE_KEY][TREE_HIDDEN_KEY]
+            .as_bool()
+            .unwrap_or(false)
+    }
+
+    fn tree_icons(&self) -> bool {
+        self.data[TREE_KEY][TREE_ICONS_KEY]
+            .as_bool()
+            .unwrap_or(false)
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        match key.split('.').fold(&self.data, |node, key| &node[key]) {
+            Yaml::String(value) | Yaml::Real(value) => Some(value.clone()),
+            Yaml::Integer(value) => Some(value.to_string()),
+            Yaml::Boolean(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        if !PERFERENCE_KEYS.contains(&key) {
+            bail!("Unknown option: {}", key);
+        }
+        let parsed = YamlLoader::load_from_str(value)
+            .ok()
+            .and_then(|docs| docs.into_iter().next())
+            .unwrap_or(Yaml::Null);
+        let node = self
+            .node_mut(key)
+            .ok_or_else(|| format!("Unknown option: {key}"))?;
+
+        // 未设置的项按解析出的类型保存，无法解析时（如空值）按字符串保存，
+        // 已有的值只接受相同类型的新值
+        if let Yaml::Null | Yaml::BadValue = node {
+            *node = match parsed {
+                Yaml::Null | Yaml::BadValue => Yaml::String(value.to_string()),
+                parsed => parsed,
+            };
+        } else if mem::discriminant(node) == mem::discriminant(&parsed) {
+            *node = parsed;
+        } else {
+            bail!("Invalid value for {}: {}", key, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::Yaml;
+
+    use super::YamlPerferences;
+    use crate::modules::perferences::Perferences;
+
+    #[test]
+    fn unset_options_keep_the_parsed_type() {
+        let mut perferences = YamlPerferences::new(Yaml::Null);
+        perferences.set("tree.width", "40").unwrap();
+        perferences.set("tree.icons", "true").unwrap();
+        perferences.set("theme", "solarized_dark").unwrap();
+        assert_eq!(perferences.tree_width(), 40);
+        assert!(perferences.tree_icons());
+        assert_eq!(perferences.get("theme").as_deref(), Some("solarized_dark"));
+        assert!(perferences.set("tree.width", "wide").is_err());
+    }
 }
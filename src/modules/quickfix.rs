@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::location::Location;
+
+lazy_static! {
+    // gcc、clang、grep -n等工具的 `file:line:col: message` 与 `file:line: message`
+    static ref LOCATION_LINE: Regex =
+        Regex::new(r"^([^\s:]+):(\d+):(?:(\d+):)?\s*(.*)$").unwrap();
+    // rustc的诊断先给出信息，再在下一行以 `--> file:line:col` 给出位置
+    static ref RUSTC_MESSAGE: Regex =
+        Regex::new(r"^((?:error|warning)(?:\[\w+\])?: .*)$").unwrap();
+    static ref RUSTC_LOCATION: Regex = Regex::new(r"^\s*--> ([^:]+):(\d+):(\d+)$").unwrap();
+}
+
+/// 快速修复列表，保存grep结果、编译错误等位置以便逐个跳转
+#[derive(Debug, Default)]
+pub struct Quickfix {
+    title: String,
+    items: Vec<Location>,
+    // 最近跳转到的项
+    current: Option<usize>,
+}
+
+impl Quickfix {
+    /// 以新的结果替换整个列表
+    pub fn set(&mut self, title: String, items: Vec<Location>) {
+        *self = Quickfix {
+            title,
+            items,
+            current: None,
+        };
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn items(&self) -> &[Location] {
+        &self.items
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn select(&mut self, index: usize) -> Option<&Location> {
+        let item = self.items.get(index)?;
+        self.current = Some(index);
+        Some(item)
+    }
+
+    /// 下一项，尚未跳转过时为第一项
+    pub fn next(&mut self) -> Option<&Location> {
+        let index = self.current.map_or(0, |index| index + 1);
+        self.select(index)
+    }
+
+    pub fn previous(&mut self) -> Option<&Location> {
+        let index = self.current?.checked_sub(1)?;
+        self.select(index)
+    }
+}
+
+/// 从编译器等工具的输出中解析出位置，相对路径以base为起点
+///
+/// 无法识别的行被忽略。
+pub fn parse_errors(output: &str, base: &Path) -> Vec<Location> {
+    let mut locations = Vec::new();
+    let mut message: Option<&str> = None;
+    for line in output.lines() {
+        if let Some(captures) = RUSTC_LOCATION.captures(line) {
+            if let Some(message) = message.take() {
+                locations.push(Location {
+                    path: base.join(&captures[1]),
+                    line: number(&captures[2]),
+                    column: number(&captures[3]),
+                    text: message.to_string(),
+                });
+            }
+        } else if let Some(captures) = RUSTC_MESSAGE.captures(line) {
+            message = captures.get(1).map(|text| text.as_str());
+        } else if let Some(captures) = LOCATION_LINE.captures(line) {
+            locations.push(Location {
+                path: base.join(&captures[1]),
+                line: number(&captures[2]),
+                column: captures.get(3).map_or(0, |column| number(column.as_str())),
+                text: captures[4].to_string(),
+            });
+        }
+    }
+    locations
+}
+
+// 输出中的行号与列号从1开始
+fn number(text: &str) -> usize {
+    text.parse::<usize>().unwrap_or(1).saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::parse_errors;
+
+    #[test]
+    fn gcc_and_rustc_messages_are_parsed() {
+        let output = "\
+main.c: In function 'main':
+main.c:3:5: error: 'x' undeclared
+lib/util.c:10: warning: unused
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:2:13
+  |
+error: aborting due to 1 previous error
+make: *** [Makefile:2: all] Error 1
+";
+        let found: Vec<(String, usize, usize, String)> = parse_errors(output, Path::new("/ws"))
+            .into_iter()
+            .map(|location| {
+                (
+                    location.path.to_string_lossy().to_string(),
+                    location.line,
+                    location.column,
+                    location.text,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            [
+                ("/ws/main.c".into(), 2, 4, "error: 'x' undeclared".into()),
+                ("/ws/lib/util.c".into(), 9, 0, "warning: unused".into()),
+                (
+                    "/ws/src/main.rs".into(),
+                    1,
+                    12,
+                    "error[E0425]: cannot find value `y` in this scope".into()
+                ),
+            ]
+        );
+    }
+}
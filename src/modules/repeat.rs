@@ -0,0 +1,151 @@
+/// 一次修改对应的按键
+///
+/// 从Normal模式下的第一个按键开始，到回到Normal模式为止，
+/// 期间的插入模式输入都属于同一次修改。
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub keys: Vec<String>,
+    pub count: usize,
+    // 第一个在插入/替换模式下输入的按键
+    insert: Option<usize>,
+    // 开始时的 (buffer id, revision)
+    revision: Option<(usize, usize)>,
+}
+
+impl Change {
+    /// 以count次回放时的按键
+    ///
+    /// 计数由回放的第一个命令使用；插入会话中输入的内容重复count次，
+    /// 离开插入模式的最后一个按键只回放一次。
+    pub fn keys_with_count(&self, count: usize) -> Vec<String> {
+        let start = match self.insert {
+            Some(start) if count > 1 && start < self.keys.len() => start,
+            _ => return self.keys.clone(),
+        };
+        let (enter, rest) = self.keys.split_at(start);
+        let (typed, leave) = rest.split_at(rest.len() - 1);
+        let mut keys = enter.to_vec();
+        for _ in 0..count {
+            keys.extend_from_slice(typed);
+        }
+        keys.extend_from_slice(leave);
+        keys
+    }
+}
+
+/// 记录最近一次修改buffer的命令，供 `.` 回放
+#[derive(Debug, Default)]
+pub struct Repeat {
+    collecting: Option<Change>,
+    last: Option<Change>,
+    pub replaying: bool,
+}
+
+impl Repeat {
+    /// 记录一个按键，`normal` 与 `editing` 分别表示按键发生在Normal模式与插入/替换模式下
+    pub fn push(
+        &mut self,
+        key: String,
+        normal: bool,
+        editing: bool,
+        count: usize,
+        revision: Option<(usize, usize)>,
+    ) {
+        if key.is_empty() {
+            return;
+        }
+
+        match self.collecting.as_mut() {
+            Some(change) => {
+                if editing && change.insert.is_none() {
+                    change.insert = Some(change.keys.len());
+                }
+                change.keys.push(key)
+            }
+            // 计数由cmd_counter记录
+            None if normal && !Self::is_digit(&key) => {
+                self.collecting = Some(Change {
+                    keys: vec![key],
+                    count,
+                    insert: None,
+                    revision,
+                })
+            }
+            None => {}
+        }
+    }
+
+    /// 回到Normal模式时结束记录，同一个buffer被修改过才会成为最近一次修改
+    pub fn finish(&mut self, revision: Option<(usize, usize)>) {
+        if let Some(change) = self.collecting.take() {
+            let changed = match (change.revision, revision) {
+                (Some((start_id, start)), Some((id, current))) => start_id == id && current > start,
+                _ => false,
+            };
+            if changed {
+                self.last = Some(change);
+            }
+        }
+    }
+
+    pub fn discard(&mut self) {
+        self.collecting = None;
+    }
+
+    pub fn last(&self) -> Option<&Change> {
+        self.last.as_ref()
+    }
+
+    /// `.` 指定新的计数后，之后的 `.` 沿用该计数
+    pub fn set_last_count(&mut self, count: usize) {
+        if let Some(change) = self.last.as_mut() {
+            change.count = count;
+        }
+    }
+
+    fn is_digit(key: &str) -> bool {
+        key.len() == 1 && key.chars().all(|c| c.is_ascii_digit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Repeat;
+
+    #[test]
+    fn insert_session_is_one_change() {
+        let mut repeat = Repeat::default();
+        repeat.push("3".to_string(), true, false, 0, Some((0, 1)));
+        for key in ["i", "h", "i", "escape"] {
+            repeat.push(key.to_string(), key == "i", key != "i", 3, Some((0, 1)));
+        }
+        repeat.finish(Some((0, 3)));
+
+        let change = repeat.last().unwrap();
+        assert_eq!(change.keys, vec!["i", "h", "i", "escape"]);
+        assert_eq!(change.count, 3);
+        assert_eq!(
+            change.keys_with_count(2),
+            vec!["i", "h", "i", "h", "i", "escape"]
+        );
+    }
+
+    #[test]
+    fn commands_repeat_through_count() {
+        let mut repeat = Repeat::default();
+        for key in ["d", "d"] {
+            repeat.push(key.to_string(), key == "d", false, 0, Some((0, 1)));
+        }
+        repeat.finish(Some((0, 2)));
+
+        assert_eq!(repeat.last().unwrap().keys_with_count(3), vec!["d", "d"]);
+    }
+
+    #[test]
+    fn commands_without_change_are_ignored() {
+        let mut repeat = Repeat::default();
+        repeat.push("j".to_string(), true, false, 0, Some((0, 1)));
+        repeat.finish(Some((0, 1)));
+        assert!(repeat.last().is_none());
+    }
+}
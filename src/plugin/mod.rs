@@ -1,9 +1,17 @@
 use dlopen2::wrapper::{Container, WrapperApi};
 use held_core::{
-    interface::ApplicationInterface, plugin::Plugin, view::render::ContentRenderBuffer,
+    interface::ApplicationInterface,
+    plugin::Plugin,
+    view::{
+        render::{overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+    },
 };
 
+use crate::get_application;
+
 pub mod system;
+pub mod wasm;
 
 #[derive(WrapperApi)]
 pub struct PluginApi {
@@ -30,6 +38,7 @@ impl Plugin for PluginInstance {
     }
 
     fn init(&self) {
+        unsafe { self.container.init_plugin_application(get_application()) };
         self.plugin.init()
     }
 
@@ -40,4 +49,12 @@ impl Plugin for PluginInstance {
     fn on_render_content(&self) -> Vec<ContentRenderBuffer> {
         self.plugin.on_render_content()
     }
+
+    fn on_render_overlays(&self) -> Vec<Overlay> {
+        self.plugin.on_render_overlays()
+    }
+
+    fn on_render_status_line(&self) -> Vec<StatusSegment> {
+        self.plugin.on_render_status_line()
+    }
 }
@@ -1,15 +1,28 @@
-use crate::{errors::*, get_application};
+use crate::errors::*;
 use dlopen2::wrapper::Container;
-use held_core::{plugin::Plugin, view::render::ContentRenderBuffer};
-use std::{collections::HashMap, path::PathBuf, rc::Rc};
+use held_core::{
+    plugin::Plugin,
+    view::{
+        render::{overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+    },
+};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 use walkdir::WalkDir;
 
 use crate::plugin::PluginApi;
 
-use super::PluginInstance;
+use super::{wasm::WasmPluginInstance, PluginInstance};
+
+const WASM_PLUGIN_EXTENSION: &str = "wasm";
 
 pub struct PluginSystem {
-    plugins: HashMap<&'static str, Rc<PluginInstance>>,
+    plugins: HashMap<&'static str, Rc<dyn Plugin>>,
 }
 
 unsafe impl Send for PluginSystem {}
@@ -33,7 +46,12 @@ impl PluginSystem {
             if let Ok(entry) = entry {
                 if entry.file_type().is_file() {
                     let path = entry.into_path();
-                    if let Err(e) = unsafe { self.load_pulgin(&path) } {
+                    let result = if path.extension() == Some(OsStr::new(WASM_PLUGIN_EXTENSION)) {
+                        self.load_wasm_pulgin(&path)
+                    } else {
+                        unsafe { self.load_pulgin(&path) }
+                    };
+                    if let Err(e) = result {
                         error!("load pulgin: {:?}, load error: {e:?}", path)
                     }
                 }
@@ -51,6 +69,12 @@ impl PluginSystem {
         );
         Ok(())
     }
+
+    pub fn load_wasm_pulgin(&mut self, pulgin_path: &Path) -> Result<()> {
+        let plugin = WasmPluginInstance::load(pulgin_path)?;
+        self.plugins.insert(plugin.name(), Rc::new(plugin));
+        Ok(())
+    }
 }
 
 impl Plugin for PluginSystem {
@@ -60,7 +84,6 @@ impl Plugin for PluginSystem {
 
     fn init(&self) {
         for (_, plugin) in self.plugins.iter() {
-            unsafe { plugin.container.init_plugin_application(get_application()) };
             plugin.init();
         }
     }
@@ -79,4 +102,22 @@ impl Plugin for PluginSystem {
 
         ret
     }
+
+    fn on_render_overlays(&self) -> Vec<Overlay> {
+        let mut ret = vec![];
+        for plugin in self.plugins.values() {
+            ret.append(&mut plugin.on_render_overlays());
+        }
+
+        ret
+    }
+
+    fn on_render_status_line(&self) -> Vec<StatusSegment> {
+        let mut ret = vec![];
+        for plugin in self.plugins.values() {
+            ret.append(&mut plugin.on_render_status_line());
+        }
+
+        ret
+    }
 }
@@ -0,0 +1,400 @@
+use std::{cell::RefCell, fs, path::Path};
+
+use held_core::{
+    interface::ApplicationInterface,
+    plugin::Plugin,
+    utils::position::Position,
+    view::{
+        render::{overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+    },
+    wasm::{self, abi},
+};
+use wasmi::{Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+use crate::{errors::*, get_application};
+
+/// 单次调用插件函数可消耗的燃料上限，防止插件死循环卡住编辑器
+const CALL_FUEL: u64 = 50_000_000;
+
+/// 宿主函数通过它取得编辑器，测试中可以替换为其他实现
+type HostApplication = fn() -> &'static mut dyn ApplicationInterface;
+
+/// `held:plugin/host` 接口中的函数在编辑器上的实现
+type HostFunction = fn(&mut dyn ApplicationInterface);
+type PositionFunction = fn(&dyn ApplicationInterface) -> Position;
+
+/// 以wasm模块形式加载的插件
+///
+/// 插件运行在wasmi解释器的沙箱中，只能通过 `held_core/wit/plugin.wit` 约定的宿主函数与编辑器交互，
+/// 插件内的任何错误（trap、燃料耗尽、越界访问、非法的返回值）都只会被记录，不会影响编辑器。
+pub struct WasmPluginInstance {
+    name: &'static str,
+    store: RefCell<Store<HostApplication>>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl WasmPluginInstance {
+    pub fn load(path: &Path) -> Result<WasmPluginInstance> {
+        Self::load_with_host(path, || get_application())
+    }
+
+    fn load_with_host(path: &Path, host: HostApplication) -> Result<WasmPluginInstance> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &fs::read(path)?[..]).map_err(wasm_error)?;
+
+        let mut store = Store::new(&engine, host);
+        store.set_fuel(CALL_FUEL).map_err(wasm_error)?;
+        let mut linker = Linker::new(&engine);
+        Self::link_host_functions(&mut linker)?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(wasm_error)?;
+        let memory = instance
+            .get_memory(&store, wasm::EXPORT_MEMORY)
+            .ok_or("wasm plugin doesn't export its memory")?;
+
+        let mut plugin = WasmPluginInstance {
+            name: "",
+            store: RefCell::new(store),
+            instance,
+            memory,
+        };
+
+        let name = plugin.call_and_lift(wasm::EXPORT_NAME, abi::lift_string)?;
+        // 插件在整个运行期间都不会被卸载
+        plugin.name = Box::leak(name.into_boxed_str());
+
+        Ok(plugin)
+    }
+
+    fn link_host_functions(linker: &mut Linker<HostApplication>) -> Result<()> {
+        let host_functions: [(&str, HostFunction); 15] = [
+            ("exit", |app| app.exit()),
+            ("to-insert-mode", |app| app.to_insert_mode()),
+            ("to-normal-mode", |app| app.to_normal_mode()),
+            ("insert-char", |app| app.insert_char()),
+            ("new-line", |app| app.new_line()),
+            ("insert-tab", |app| app.insert_tab()),
+            ("move-left", |app| app.move_left()),
+            ("move-right", |app| app.move_right()),
+            ("move-up", |app| app.move_up()),
+            ("move-down", |app| app.move_down()),
+            ("move-to-start-of-line", |app| app.move_to_start_of_line()),
+            ("scroll-to-cursor", |app| app.scroll_to_cursor()),
+            ("scroll-to-center", |app| app.scroll_to_center()),
+            ("save-file", |app| app.save_file()),
+            ("undo", |app| app.undo()),
+        ];
+
+        for (name, function) in host_functions {
+            linker
+                .func_wrap(
+                    wasm::HOST_MODULE,
+                    name,
+                    move |caller: Caller<'_, HostApplication>| function(caller.data()()),
+                )
+                .map_err(wasm_error)?;
+        }
+
+        let position_functions: [(&str, PositionFunction); 2] = [
+            ("screen-cursor-position", |app| app.screen_cursor_position()),
+            ("cursor-position", |app| app.cursor_position()),
+        ];
+
+        for (name, function) in position_functions {
+            linker
+                .func_wrap(
+                    wasm::HOST_MODULE,
+                    name,
+                    move |mut caller: Caller<'_, HostApplication>,
+                          ret: i32|
+                          -> std::result::Result<(), wasmi::Error> {
+                        let position = function(caller.data()());
+                        let memory = caller
+                            .get_export(wasm::EXPORT_MEMORY)
+                            .and_then(Extern::into_memory)
+                            .ok_or_else(|| wasmi::Error::new("missing memory export"))?;
+                        abi::lower_position(
+                            memory.data_mut(&mut caller),
+                            ret as u32 as usize,
+                            position,
+                        )
+                        .ok_or_else(|| wasmi::Error::new("return pointer out of bounds"))
+                    },
+                )
+                .map_err(wasm_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn call<Params, Results>(&self, name: &str, params: Params) -> Result<Results>
+    where
+        Params: wasmi::WasmParams,
+        Results: wasmi::WasmResults,
+    {
+        let mut store = self.store.borrow_mut();
+        let func: TypedFunc<Params, Results> = self
+            .instance
+            .get_typed_func(&*store, name)
+            .map_err(wasm_error)?;
+        store.set_fuel(CALL_FUEL).map_err(wasm_error)?;
+        func.call(&mut *store, params).map_err(wasm_error)
+    }
+
+    /// 调用返回 `string` 或 `list` 的导出函数，从插件内存中读取返回值之后让插件释放它
+    fn call_and_lift<T>(
+        &self,
+        name: &str,
+        lift: impl FnOnce(&[u8], usize) -> Option<T>,
+    ) -> Result<T> {
+        let ret = self.call::<(), i32>(name, ())?;
+        let value = lift(self.memory.data(&*self.store.borrow()), ret as u32 as usize);
+        self.call::<i32, ()>(&format!("{}{name}", wasm::POST_RETURN_PREFIX), ret)?;
+        value.ok_or_else(|| format!("malformed return value of {name}").into())
+    }
+
+    fn render_content(
+        &self,
+        screen_width: usize,
+        screen_height: usize,
+    ) -> Result<Vec<ContentRenderBuffer>> {
+        self.call_and_lift(wasm::EXPORT_ON_RENDER_CONTENT, |memory, ret| {
+            abi::lift_render_buffers(memory, ret, screen_width, screen_height)
+        })
+    }
+
+    fn call_or_log(&self, name: &str) {
+        if let Err(e) = self.call::<(), ()>(name, ()) {
+            error!("wasm plugin {}: call {name} failed: {e:?}", self.name);
+        }
+    }
+}
+
+impl Plugin for WasmPluginInstance {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn init(&self) {
+        self.call_or_log(wasm::EXPORT_INIT);
+    }
+
+    fn deinit(&self) {
+        self.call_or_log(wasm::EXPORT_DEINIT);
+    }
+
+    fn on_render_content(&self) -> Vec<ContentRenderBuffer> {
+        // 与渲染器使用同一个终端的大小，无界面模式下同样可用
+        let monitor = &get_application().monitor;
+        let buffers = monitor
+            .width()
+            .and_then(|width| Ok((width, monitor.height()?)))
+            .and_then(|(width, height)| self.render_content(width, height));
+
+        match buffers {
+            Ok(buffers) => buffers,
+            Err(e) => {
+                error!("wasm plugin {}: on_render_content failed: {e:?}", self.name);
+                vec![]
+            }
+        }
+    }
+
+    fn on_render_overlays(&self) -> Vec<Overlay> {
+        match self.call_and_lift(wasm::EXPORT_ON_RENDER_OVERLAYS, abi::lift_overlays) {
+            Ok(overlays) => overlays,
+            Err(e) => {
+                error!(
+                    "wasm plugin {}: on_render_overlays failed: {e:?}",
+                    self.name
+                );
+                vec![]
+            }
+        }
+    }
+
+    fn on_render_status_line(&self) -> Vec<StatusSegment> {
+        match self.call_and_lift(
+            wasm::EXPORT_ON_RENDER_STATUS_LINE,
+            abi::lift_status_segments,
+        ) {
+            Ok(segments) => segments,
+            Err(e) => {
+                error!(
+                    "wasm plugin {}: on_render_status_line failed: {e:?}",
+                    self.name
+                );
+                vec![]
+            }
+        }
+    }
+}
+
+fn wasm_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::from(format!("wasm plugin error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use held_core::{
+        interface::{
+            app::App, buffer::Buffer, cursor::Cursor, monitor::Monitor, workspace::Workspace,
+            ApplicationInterface,
+        },
+        plugin::Plugin,
+        utils::position::Position,
+        view::{
+            colors::{Color, Colors},
+            render::overlay::Overlay,
+            style::CharStyle,
+        },
+    };
+
+    use super::WasmPluginInstance;
+
+    // 插件init时调用move_down的次数
+    static MOVES_DOWN: AtomicUsize = AtomicUsize::new(0);
+
+    // 光标固定在第3行第6列的编辑器，只记录move_down的调用
+    struct TestApplication;
+
+    impl App for TestApplication {
+        fn exit(&mut self) {}
+
+        fn to_insert_mode(&mut self) {}
+
+        fn to_normal_mode(&mut self) {}
+    }
+
+    impl Buffer for TestApplication {
+        fn insert_char(&mut self) {}
+
+        fn new_line(&mut self) {}
+
+        fn insert_tab(&mut self) {}
+    }
+
+    impl Cursor for TestApplication {
+        fn move_left(&mut self) {}
+
+        fn move_right(&mut self) {}
+
+        fn move_up(&mut self) {}
+
+        fn move_down(&mut self) {
+            MOVES_DOWN.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn move_to_start_of_line(&mut self) {}
+
+        fn screen_cursor_position(&self) -> Position {
+            Position::new(2, 5)
+        }
+
+        fn cursor_position(&self) -> Position {
+            Position::new(2, 5)
+        }
+    }
+
+    impl Monitor for TestApplication {
+        fn scroll_to_cursor(&mut self) {}
+
+        fn scroll_to_center(&mut self) {}
+    }
+
+    impl Workspace for TestApplication {
+        fn save_file(&mut self) {}
+
+        fn undo(&mut self) {}
+    }
+
+    impl ApplicationInterface for TestApplication {}
+
+    // 预先编译好的test/test_render_plugin，修改插件后在仓库根目录下重新生成：
+    // cargo build --release -p test_render_plugin --target wasm32-unknown-unknown
+    // cp target/wasm32-unknown-unknown/release/test_render_plugin.wasm test/test_render_plugin/
+    fn test_plugin() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test/test_render_plugin/test_render_plugin.wasm")
+    }
+
+    #[test]
+    fn load_and_render_wasm_plugin() {
+        let path = test_plugin();
+        // TestApplication为零大小类型，leak不会产生分配
+        let plugin =
+            WasmPluginInstance::load_with_host(&path, || Box::leak(Box::new(TestApplication)))
+                .unwrap();
+        assert_eq!(plugin.name(), "render test plugin");
+        plugin.init();
+        assert_eq!(MOVES_DOWN.load(Ordering::SeqCst), 1);
+
+        let buffers = plugin.render_content(80, 24).unwrap();
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(buffers[0].rectangle.width, 4);
+        let cell = buffers[0].cells[0].as_ref().unwrap();
+        assert_eq!(cell.content, 'w');
+        assert_eq!(
+            cell.colors,
+            Colors::Custom(Color::Rgb { r: 1, g: 2, b: 3 }, Color::AnsiValue(9))
+        );
+        assert!(plugin.render_content(3, 24).is_err());
+
+        let overlays = plugin.on_render_overlays();
+        assert_eq!(overlays.len(), 4);
+        assert_eq!(
+            overlays[0],
+            Overlay::Popup {
+                anchor: Position::new(2, 5),
+                lines: vec![" 3:6 ".to_string()],
+                style: CharStyle::Bold,
+                colors: Colors::Warning,
+            }
+        );
+        assert_eq!(
+            overlays[1],
+            Overlay::VirtualText {
+                line: 2,
+                text: "render test".to_string(),
+                style: CharStyle::Italic,
+                colors: Colors::CustomForeground(Color::DarkGrey),
+            }
+        );
+        assert_eq!(
+            overlays[2],
+            Overlay::InlayHint {
+                position: Position::new(2, 5),
+                text: "|".to_string(),
+                style: CharStyle::Default,
+                colors: Colors::CustomFocusedForeground(Color::Cyan),
+            }
+        );
+        assert_eq!(
+            overlays[3],
+            Overlay::GutterSign {
+                line: 2,
+                sign: '>',
+                style: CharStyle::Bold,
+                colors: Colors::Warning,
+            }
+        );
+
+        let segments = plugin.on_render_status_line();
+        assert_eq!(segments[0].name, "render_test");
+        assert_eq!(segments[0].content, " wasm ");
+        assert_eq!(segments[0].style, CharStyle::Bold);
+        assert_eq!(segments[0].colors, Colors::Warning);
+    }
+}
@@ -2,6 +2,7 @@ pub mod colors;
 pub mod monitor;
 pub mod presenter;
 pub mod render;
-pub mod status_data;
+pub mod status_line;
 pub mod terminal;
 pub mod theme_loadler;
+pub mod tree_panel;
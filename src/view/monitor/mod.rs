@@ -1,20 +1,25 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc, time::Duration};
 
 use super::{
     presenter::Presenter,
-    render::{render_buffer::CachedRenderBuffer, render_state::RenderState},
+    render::{render_buffer::CachedRenderBuffer, render_state::RenderState, screen_map::ScreenMap},
     terminal::{cross_terminal::CrossTerminal, Terminal},
     theme_loadler::ThemeLoader,
+    tree_panel::TreePanel,
 };
 use crate::errors::*;
+use crate::modules::messages::Message;
 use crate::modules::perferences::Perferences;
 use crate::{buffer::Buffer, plugin::system::PluginSystem};
-use crossterm::event::{Event, KeyEvent};
+use crossterm::event::{Event, KeyEvent, MouseEvent};
 use scroll_controller::ScrollController;
 use syntect::highlighting::{Theme, ThemeSet};
 
 pub mod scroll_controller;
 
+// 显示目录树面板时至少留给编辑区的宽度
+const MIN_EDITOR_WIDTH: usize = 20;
+
 /// 管理所有的显示
 pub struct Monitor {
     pub terminal: Arc<Box<dyn Terminal>>,
@@ -23,8 +28,18 @@ pub struct Monitor {
     scroll_controllers: HashMap<usize, ScrollController>,
     render_caches: HashMap<usize, Rc<RefCell<HashMap<usize, RenderState>>>>,
     pub last_key: Option<KeyEvent>,
+    pub last_mouse: Option<MouseEvent>,
+    pub last_paste: Option<String>,
+    // 最近一次渲染buffer时屏幕与buffer位置的对应关系
+    pub screen_map: ScreenMap,
+    // 等待后续按键的序列前缀，显示在状态栏上
+    pub pending_keys: String,
+    // 显示在状态栏上的消息
+    pub message: Option<Message>,
     pub cached_render_buffer: Rc<RefCell<CachedRenderBuffer>>,
     pub plugin_system: Rc<RefCell<PluginSystem>>,
+    // 停靠在左侧的目录树
+    pub tree_panel: TreePanel,
 }
 
 impl Monitor {
@@ -32,18 +47,31 @@ impl Monitor {
         perference: Rc<RefCell<dyn Perferences>>,
         plugin_system: Rc<RefCell<PluginSystem>>,
     ) -> Result<Monitor> {
-        let terminal = CrossTerminal::new()?;
+        Self::with_terminal(perference, plugin_system, Box::new(CrossTerminal::new()?))
+    }
+
+    pub fn with_terminal(
+        perference: Rc<RefCell<dyn Perferences>>,
+        plugin_system: Rc<RefCell<PluginSystem>>,
+        terminal: Box<dyn Terminal>,
+    ) -> Result<Monitor> {
         let cached_render_buffer = CachedRenderBuffer::new(terminal.width()?, terminal.height()?);
         let theme_set = ThemeLoader::new(perference.borrow().theme_path()?).load()?;
         Ok(Monitor {
-            terminal: Arc::new(Box::new(terminal)),
+            terminal: Arc::new(terminal),
             theme_set,
             perference,
             scroll_controllers: HashMap::new(),
             render_caches: HashMap::new(),
             last_key: None,
+            last_mouse: None,
+            last_paste: None,
+            screen_map: ScreenMap::default(),
+            pending_keys: String::new(),
+            message: None,
             cached_render_buffer: Rc::new(RefCell::new(cached_render_buffer)),
             plugin_system,
+            tree_panel: TreePanel::default(),
         })
     }
 
@@ -74,12 +102,26 @@ impl Monitor {
 
     pub fn listen(&mut self) -> Result<Event> {
         let ev = self.terminal.listen()?;
-        if let Event::Key(key) = ev {
-            self.last_key.replace(key);
+        match ev {
+            Event::Key(key) => {
+                self.last_key.replace(key);
+            }
+            Event::Mouse(mouse) => {
+                self.last_mouse.replace(mouse);
+            }
+            Event::Paste(ref text) => {
+                self.last_paste.replace(text.clone());
+            }
+            _ => {}
         }
         Ok(ev)
     }
 
+    /// 等待至多timeout时长，返回是否有事件可读
+    pub fn poll(&self, timeout: Duration) -> Result<bool> {
+        self.terminal.poll(timeout)
+    }
+
     pub fn width(&self) -> Result<usize> {
         self.terminal.width()
     }
@@ -88,10 +130,29 @@ impl Monitor {
         self.terminal.height()
     }
 
+    /// 目录树面板占用的宽度，面板隐藏或屏幕太窄时为0
+    pub fn tree_panel_width(&self) -> Result<usize> {
+        if !self.tree_panel.is_visible() {
+            return Ok(0);
+        }
+        let width = self
+            .perference
+            .borrow()
+            .tree_width()
+            .min(self.width()?.saturating_sub(MIN_EDITOR_WIDTH));
+        // 至少要能显示一个字符与分隔线
+        Ok(if width < 2 { 0 } else { width })
+    }
+
     pub fn get_theme(&self, name: &String) -> Option<Theme> {
         self.theme_set.themes.get(name).cloned()
     }
 
+    /// 所有可用主题的名字
+    pub fn theme_names(&self) -> Vec<String> {
+        self.theme_set.themes.keys().cloned().collect()
+    }
+
     pub fn first_theme(&self) -> Option<Theme> {
         self.theme_set
             .themes
@@ -107,6 +168,25 @@ impl Monitor {
         self.render_caches.get(&buffer.id.unwrap()).unwrap()
     }
 
+    /// 按终端当前的大小重新分配渲染缓存并清屏，下一次渲染会重绘整个屏幕
+    pub fn redraw(&mut self) -> Result<()> {
+        *self.cached_render_buffer.borrow_mut() =
+            CachedRenderBuffer::new(self.width()?, self.height()?);
+        self.screen_map = ScreenMap::default();
+        self.terminal.clear()
+    }
+
+    /// 交出终端，用于挂起或运行外部命令
+    pub fn suspend(&mut self) {
+        self.terminal.suspend();
+    }
+
+    /// 重新接管终端并重绘整个屏幕
+    pub fn resume(&mut self) -> Result<()> {
+        self.terminal.resume()?;
+        self.redraw()
+    }
+
     pub fn get_scroll_controller(&mut self, buffer: &Buffer) -> &mut ScrollController {
         self.scroll_controllers
             .entry(buffer.id.unwrap())
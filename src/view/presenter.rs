@@ -7,16 +7,21 @@ use super::{
         lexeme_mapper::LexemeMapper,
         render_buffer::{Cell, RenderBuffer},
     },
-    status_data::StatusLineData,
+    status_line::{fit_width, PlacedSegment, StatusLine, MODE_SEGMENT},
 };
 use crate::{
-    buffer::Buffer, errors::*, util::line_iterator::LineIterator, view::render::renderer::Renderer,
+    buffer::Buffer,
+    errors::*,
+    util::line_iterator::LineIterator,
+    view::render::renderer::{Renderer, Viewport},
 };
 use held_core::{
+    plugin::Plugin,
     utils::{position::Position, range::Range},
     view::{colors::Colors, style::CharStyle},
 };
 use syntect::{highlighting::Theme, parsing::SyntaxSet};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Presenter<'a> {
     view: &'a mut Monitor,
@@ -27,15 +32,14 @@ pub struct Presenter<'a> {
 
 impl<'a> Presenter<'a> {
     pub fn new(monitor: &mut Monitor) -> Result<Presenter> {
-        let theme_name = monitor.perference.borrow().theme_name();
-        let mut theme = monitor
-            .first_theme()
+        // 找不到配置的主题时使用第一个主题，启动时已经提示过
+        let theme = monitor
+            .perference
+            .borrow()
+            .theme_name()
+            .and_then(|theme_name| monitor.get_theme(&theme_name))
+            .or_else(|| monitor.first_theme())
             .ok_or_else(|| format!("Couldn't find anyone theme"))?;
-        if let Some(theme_name) = theme_name {
-            theme = monitor
-                .get_theme(&theme_name)
-                .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
-        }
         let present_buffer = RenderBuffer::new(
             monitor.width()?,
             monitor.height()?,
@@ -53,6 +57,10 @@ impl<'a> Presenter<'a> {
         self.cursor_position = Some(position);
     }
 
+    pub fn hide_cursor(&mut self) {
+        self.cursor_position = None;
+    }
+
     pub fn present(&self) -> Result<()> {
         for (position, cell) in self.present_buffer.iter() {
             self.view
@@ -71,37 +79,46 @@ impl<'a> Presenter<'a> {
         Ok(())
     }
 
-    pub fn print_status_line(&mut self, datas: &[StatusLineData]) -> Result<()> {
+    /// 合并插件片段后按照配置布局状态栏，返回各片段的位置
+    pub fn print_status_line(&mut self, status_line: StatusLine) -> Result<Vec<PlacedSegment>> {
         let line_width = self.view.terminal.width()?;
         let line = self.view.terminal.height()? - 1;
 
-        let count = datas.len();
-        let mut offset = 0;
-        // 从左往右输出，最后一个参数在最后
-        for (index, data) in datas.iter().enumerate() {
-            let content = match count {
-                1 => {
-                    format!("{:width$}", data.content, width = line_width)
-                }
-                _ => {
-                    if index == count - 1 {
-                        format!(
-                            "{:width$}",
-                            data.content,
-                            width = line_width.saturating_sub(offset)
-                        )
-                    } else {
-                        data.content.to_owned()
-                    }
-                }
-            };
-
-            let len = content.len();
-            self.print(&Position { line, offset }, data.style, data.color, content);
-            offset += len;
+        let mut status_line = status_line.pending_keys(&self.view.pending_keys);
+        let layout = self.view.perference.borrow().status_line_layout();
+        let plugin_segments = self.view.plugin_system.borrow().on_render_status_line();
+        status_line.plugin_segments(plugin_segments, &layout);
+
+        let placed = status_line.layout(&layout, line_width);
+        for segment in placed.iter() {
+            self.print(
+                &Position {
+                    line,
+                    offset: segment.offset,
+                },
+                segment.data.style,
+                segment.data.color,
+                segment.data.content.clone(),
+            );
         }
 
-        Ok(())
+        // 消息覆盖模式名之后的区域
+        if let Some(message) = self.view.message.clone() {
+            let offset = placed
+                .iter()
+                .find(|segment| segment.name.as_deref() == Some(MODE_SEGMENT))
+                .map_or(0, |segment| segment.offset + segment.width);
+            let width = line_width.saturating_sub(offset);
+            let content = fit_width(&format!(" {}", message.content), width);
+            self.print(
+                &Position { line, offset },
+                CharStyle::Bold,
+                message.colors(),
+                content,
+            );
+        }
+
+        Ok(placed)
     }
 
     // 按照预设渲染buffer
@@ -115,20 +132,38 @@ impl<'a> Presenter<'a> {
     ) -> Result<()> {
         let scroll_offset = self.view.get_scroll_controller(buffer).line_offset();
         let lines = LineIterator::new(&buffer_data);
+        let left = self.view.tree_panel_width()?;
+        if left > 0 {
+            self.print_tree_panel(left)?;
+        }
 
-        let cursor_position = Renderer::new(
-            buffer,
-            &mut self.present_buffer,
-            &**self.view.terminal,
-            &*self.view.perference.borrow(),
-            highlights,
-            self.view.get_render_cache(buffer),
-            &self.theme,
-            syntax_set,
+        let viewport = Viewport {
             scroll_offset,
-            &mut self.view.plugin_system.borrow_mut(),
-        )
-        .render(lines, lexeme_mapper)?;
+            left,
+            width: self.present_buffer.width().saturating_sub(left),
+        };
+
+        let (cursor_position, screen_map) = {
+            let perference = self.view.perference.borrow();
+            let mut plugin_system = self.view.plugin_system.borrow_mut();
+            let mut renderer = Renderer::new(
+                buffer,
+                &mut self.present_buffer,
+                &**self.view.terminal,
+                &*perference,
+                highlights,
+                self.view.get_render_cache(buffer),
+                &self.theme,
+                syntax_set,
+                viewport,
+                &mut plugin_system,
+            );
+            (
+                renderer.render(lines, lexeme_mapper)?,
+                renderer.take_screen_map(),
+            )
+        };
+        self.view.screen_map = screen_map;
 
         match cursor_position {
             Some(position) => self.set_cursor(position),
@@ -138,6 +173,71 @@ impl<'a> Presenter<'a> {
         Ok(())
     }
 
+    /// 在屏幕左侧width列中绘制目录树面板，未占满屏幕时最后一列为分隔线
+    pub fn print_tree_panel(&mut self, width: usize) -> Result<()> {
+        let height = self.view.height()? - 1;
+        let docked = width < self.present_buffer.width();
+        let rows = self
+            .view
+            .tree_panel
+            .rows(height, width - usize::from(docked));
+        for (line, row) in rows.into_iter().enumerate() {
+            self.print_str(
+                &Position { line, offset: 0 },
+                row.style,
+                row.colors,
+                &row.text,
+            );
+            if let Some((marker, colors)) = row.marker {
+                let offset = width - usize::from(docked) - 1;
+                self.print_str(&Position { line, offset }, CharStyle::Bold, colors, marker);
+            }
+        }
+        if !docked {
+            return Ok(());
+        }
+        for line in 0..height {
+            self.print(
+                &Position {
+                    line,
+                    offset: width - 1,
+                },
+                CharStyle::Default,
+                Colors::Focused,
+                "│",
+            );
+        }
+        Ok(())
+    }
+
+    /// 从position开始每个字素占一个单元格打印content，用于覆盖在buffer之上的内容，
+    /// 超出屏幕宽度的部分被截断，返回打印的宽度
+    pub fn print_str(
+        &mut self,
+        position: &Position,
+        style: CharStyle,
+        colors: Colors,
+        content: &str,
+    ) -> usize {
+        let mut offset = position.offset;
+        for grapheme in content.graphemes(true) {
+            if offset >= self.present_buffer.width() {
+                break;
+            }
+            self.print(
+                &Position {
+                    line: position.line,
+                    offset,
+                },
+                style,
+                colors,
+                grapheme.to_string(),
+            );
+            offset += 1;
+        }
+        offset - position.offset
+    }
+
     pub fn print<C>(&mut self, position: &Position, style: CharStyle, colors: Colors, content: C)
     where
         C: Into<Cow<'a, str>> + Debug,
@@ -1,5 +1,7 @@
 pub mod lexeme_mapper;
 pub mod line_number_string_iter;
+pub mod overlays;
 pub mod render_buffer;
 pub mod render_state;
 pub mod renderer;
+pub mod screen_map;
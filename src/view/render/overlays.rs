@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use held_core::{
+    utils::position::Position,
+    view::{colors::Colors, render::overlay::Overlay, style::CharStyle},
+};
+
+pub type StyledText = (String, CharStyle, Colors);
+
+#[derive(Debug, Clone)]
+pub struct Popup {
+    pub anchor: Position,
+    pub lines: Vec<String>,
+    pub style: CharStyle,
+    pub colors: Colors,
+    /// 渲染过程中记录的锚点屏幕坐标，锚点不可见时为None
+    pub screen_anchor: Option<Position>,
+}
+
+/// 按buffer位置索引插件提供的覆盖层，供Renderer在逐字符渲染时查询
+#[derive(Debug, Default)]
+pub struct RenderOverlays {
+    gutter_signs: HashMap<usize, (char, CharStyle, Colors)>,
+    virtual_texts: HashMap<usize, Vec<StyledText>>,
+    inlay_hints: HashMap<(usize, usize), Vec<StyledText>>,
+    popups: Vec<Popup>,
+}
+
+impl RenderOverlays {
+    pub fn new(overlays: Vec<Overlay>) -> RenderOverlays {
+        let mut ret = RenderOverlays::default();
+        for overlay in overlays {
+            match overlay {
+                Overlay::Popup {
+                    anchor,
+                    lines,
+                    style,
+                    colors,
+                } => ret.popups.push(Popup {
+                    anchor,
+                    lines,
+                    style,
+                    colors,
+                    screen_anchor: None,
+                }),
+                Overlay::VirtualText {
+                    line,
+                    text,
+                    style,
+                    colors,
+                } => ret
+                    .virtual_texts
+                    .entry(line)
+                    .or_default()
+                    .push((text, style, colors)),
+                Overlay::InlayHint {
+                    position,
+                    text,
+                    style,
+                    colors,
+                } => ret
+                    .inlay_hints
+                    .entry((position.line, position.offset))
+                    .or_default()
+                    .push((text, style, colors)),
+                Overlay::GutterSign {
+                    line,
+                    sign,
+                    style,
+                    colors,
+                } => {
+                    // 同一行只显示最后一个标记
+                    ret.gutter_signs.insert(line, (sign, style, colors));
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn gutter_sign(&self, line: usize) -> Option<(char, CharStyle, Colors)> {
+        self.gutter_signs.get(&line).copied()
+    }
+
+    pub fn virtual_texts(&self, line: usize) -> Vec<StyledText> {
+        self.virtual_texts.get(&line).cloned().unwrap_or_default()
+    }
+
+    pub fn inlay_hints(&self, position: Position) -> Vec<StyledText> {
+        self.inlay_hints
+            .get(&(position.line, position.offset))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 记录锚定在buffer_position上的弹窗所对应的屏幕坐标
+    pub fn locate_popups(&mut self, buffer_position: Position, screen_position: Position) {
+        for popup in self.popups.iter_mut() {
+            if popup.anchor == buffer_position {
+                popup.screen_anchor = Some(screen_position);
+            }
+        }
+    }
+
+    pub fn popups(&self) -> &[Popup] {
+        &self.popups
+    }
+
+    /// 计算弹窗左上角的屏幕坐标与可以显示的行数
+    ///
+    /// 优先放在锚点的下一行，下方空间不足时放在锚点上方，两侧都放不下时放在空间较大的一侧，
+    /// 超出的行不显示，弹窗不会覆盖content_height之下的状态栏；水平方向向左收进屏幕内。
+    pub fn popup_placement(
+        screen_anchor: Position,
+        width: usize,
+        height: usize,
+        screen_width: usize,
+        content_height: usize,
+    ) -> (Position, usize) {
+        let below = content_height.saturating_sub(screen_anchor.line + 1);
+        let above = screen_anchor.line.min(content_height);
+        let (line, height) = if height <= below || below >= above {
+            (screen_anchor.line + 1, height.min(below))
+        } else {
+            let height = height.min(above);
+            (screen_anchor.line - height, height)
+        };
+
+        let origin = Position {
+            line,
+            offset: screen_anchor.offset.min(screen_width.saturating_sub(width)),
+        };
+        (origin, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use held_core::utils::position::Position;
+
+    use super::RenderOverlays;
+
+    #[test]
+    fn popup_is_placed_below_the_anchor() {
+        let placement = RenderOverlays::popup_placement(Position::new(3, 10), 5, 2, 80, 23);
+        assert_eq!(placement, (Position::new(4, 10), 2));
+    }
+
+    #[test]
+    fn popup_flips_above_at_the_bottom() {
+        let placement = RenderOverlays::popup_placement(Position::new(21, 10), 5, 2, 80, 23);
+        assert_eq!(placement, (Position::new(19, 10), 2));
+    }
+
+    #[test]
+    fn popup_is_kept_inside_the_screen() {
+        let placement = RenderOverlays::popup_placement(Position::new(0, 78), 10, 1, 80, 23);
+        assert_eq!(placement, (Position::new(1, 70), 1));
+    }
+
+    #[test]
+    fn tall_popup_does_not_cover_the_status_line() {
+        // 下方有19行，上方只有3行
+        let placement = RenderOverlays::popup_placement(Position::new(3, 0), 5, 30, 80, 23);
+        assert_eq!(placement, (Position::new(4, 0), 19));
+        // 下方只有2行，上方有20行
+        let placement = RenderOverlays::popup_placement(Position::new(20, 0), 5, 30, 80, 23);
+        assert_eq!(placement, (Position::new(0, 0), 20));
+    }
+}
@@ -114,6 +114,10 @@ impl<'a> RenderBuffer<'a> {
         self.cells = vec![Cell::default(); self.width * self.height];
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
     pub fn iter(&self) -> RenderBufferIter {
         RenderBufferIter::new(self)
     }
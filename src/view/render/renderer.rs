@@ -22,19 +22,31 @@ use syntect::parsing::{ScopeStack, SyntaxSet};
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::line_number_string_iter::LineNumberStringIter;
+use super::overlays::RenderOverlays;
 use super::render_buffer::Cell;
 use super::render_state::RenderState;
+use super::screen_map::ScreenMap;
 use super::{lexeme_mapper::LexemeMapper, render_buffer::RenderBuffer};
 
 const RENDER_CACHE_FREQUENCY: usize = 100;
 
+/// 编辑区在屏幕上的范围，左侧可能停靠着目录树面板
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Viewport {
+    // buffer中显示在第一行的行
+    pub scroll_offset: usize,
+    // 起始列与宽度
+    pub left: usize,
+    pub width: usize,
+}
+
 pub struct Renderer<'a, 'p> {
     buffer: &'a Buffer,
     render_buffer: &'a mut RenderBuffer<'p>,
     terminal: &'a dyn Terminal,
     theme: &'a Theme,
     highlight_ranges: Option<&'a [(Range, CharStyle, Colors)]>,
-    scroll_offset: usize,
+    viewport: Viewport,
     line_number_iter: LineNumberStringIter,
     content_start_of_line: usize,
     cached_render_state: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
@@ -45,6 +57,8 @@ pub struct Renderer<'a, 'p> {
     current_style: Style,
     perferences: &'a dyn Perferences,
     plugin_system: &'a mut PluginSystem,
+    overlays: RenderOverlays,
+    screen_map: ScreenMap,
 }
 
 impl<'a, 'p> Renderer<'a, 'p> {
@@ -57,17 +71,17 @@ impl<'a, 'p> Renderer<'a, 'p> {
         cached_render_state: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
         theme: &'a Theme,
         syntax_set: &'a SyntaxSet,
-        scroll_offset: usize,
+        viewport: Viewport,
         plugin_system: &'a mut PluginSystem,
     ) -> Renderer<'a, 'p> {
-        let line_number_iter = LineNumberStringIter::new(buffer, scroll_offset);
+        let line_number_iter = LineNumberStringIter::new(buffer, viewport.scroll_offset);
         let content_start_of_line = line_number_iter.width() + 1;
         Self {
             buffer,
             render_buffer,
             terminal,
             theme,
-            scroll_offset,
+            viewport,
             syntax_set,
             cached_render_state,
             screen_position: Position::default(),
@@ -79,6 +93,8 @@ impl<'a, 'p> Renderer<'a, 'p> {
             line_number_iter,
             content_start_of_line,
             plugin_system,
+            overlays: RenderOverlays::default(),
+            screen_map: ScreenMap::default(),
         }
     }
 
@@ -88,6 +104,7 @@ impl<'a, 'p> Renderer<'a, 'p> {
         mut lexeme_mapper: Option<&mut dyn LexemeMapper>,
     ) -> Result<Option<Position>> {
         self.terminal.set_cursor(None)?;
+        self.overlays = RenderOverlays::new(self.plugin_system.on_render_overlays());
         self.render_line_number();
 
         let highlighter = Highlighter::new(&self.theme);
@@ -154,6 +171,13 @@ impl<'a, 'p> Renderer<'a, 'p> {
             self.try_to_advance_to_next_line(&line_data);
         }
 
+        // 最后一行没有换行符，需要单独处理行尾
+        if self.inside_visible() {
+            self.set_cursor();
+            self.render_rest_of_line();
+        }
+
+        self.render_popups();
         self.render_plugins()?;
 
         Ok(self.cursor_position)
@@ -204,6 +228,48 @@ impl<'a, 'p> Renderer<'a, 'p> {
         Ok(())
     }
 
+    fn render_popups(&mut self) {
+        let screen_width = self.viewport.width;
+        let content_height = self.terminal.height().unwrap() - 1;
+        let popups = self.overlays.popups().to_vec();
+        for popup in popups {
+            let screen_anchor = match popup.screen_anchor {
+                Some(position) => position,
+                None => continue,
+            };
+
+            let width = popup
+                .lines
+                .iter()
+                .map(|line| line.graphemes(true).count())
+                .max()
+                .unwrap_or(0);
+            let (origin, height) = RenderOverlays::popup_placement(
+                screen_anchor,
+                width,
+                popup.lines.len(),
+                screen_width,
+                content_height,
+            );
+
+            for (index, line) in popup.lines.iter().take(height).enumerate() {
+                let mut graphemes = line.graphemes(true);
+                for offset in 0..width {
+                    let content = graphemes.next().unwrap_or(" ").to_string();
+                    self.render_cell(
+                        Position {
+                            line: origin.line + index,
+                            offset: origin.offset + offset,
+                        },
+                        popup.style,
+                        popup.colors,
+                        content,
+                    );
+                }
+            }
+        }
+    }
+
     fn mapper_comment_style(highlighter: &Highlighter) -> Style {
         highlighter.style_for_stack(
             ScopeStack::from_str("keyword")
@@ -216,17 +282,22 @@ impl<'a, 'p> Renderer<'a, 'p> {
         self.cached_render_state
             .borrow()
             .iter()
-            .filter(|(k, _)| **k < self.scroll_offset)
+            .filter(|(k, _)| **k < self.viewport.scroll_offset)
             .max_by(|a, b| a.0.cmp(b.0))
             .map(|x| (*x.0, x.1.clone()))
     }
 
+    /// 本次渲染记录的屏幕坐标与buffer位置的对应关系
+    pub fn take_screen_map(&mut self) -> ScreenMap {
+        std::mem::take(&mut self.screen_map)
+    }
+
     fn after_visible(&self) -> bool {
         self.screen_position.line >= (self.terminal.height().unwrap() - 1)
     }
 
     fn before_visible(&self) -> bool {
-        self.buffer_position.line < self.scroll_offset
+        self.buffer_position.line < self.viewport.scroll_offset
     }
 
     fn inside_visible(&self) -> bool {
@@ -234,10 +305,17 @@ impl<'a, 'p> Renderer<'a, 'p> {
     }
 
     fn set_cursor(&mut self) {
-        if self.inside_visible() && *self.buffer.cursor == self.buffer_position {
-            self.cursor_position = Some(self.screen_position);
-            get_application().state_data.cursor_state.screen_position = self.screen_position;
+        if !self.inside_visible() {
+            return;
         }
+
+        if *self.buffer.cursor == self.buffer_position {
+            let screen_position = self.absolute(self.screen_position);
+            self.cursor_position = Some(screen_position);
+            get_application().state_data.cursor_state.screen_position = screen_position;
+        }
+        self.overlays
+            .locate_popups(self.buffer_position, self.screen_position);
     }
 
     fn on_cursor_line(&self) -> bool {
@@ -263,8 +341,24 @@ impl<'a, 'p> Renderer<'a, 'p> {
     }
 
     fn render_rest_of_line(&mut self) {
+        self.render_inlay_hints();
+        self.screen_map
+            .record(self.absolute(self.screen_position), self.buffer_position);
+
+        let width = self.viewport.width;
+        for (text, style, colors) in self.overlays.virtual_texts(self.buffer_position.line) {
+            self.screen_position.offset += 1;
+            for character in text.graphemes(true) {
+                if self.screen_position.offset >= width {
+                    break;
+                }
+                self.render_cell(self.screen_position, style, colors, character.to_string());
+                self.screen_position.offset += 1;
+            }
+        }
+
         let on_cursor_line = self.on_cursor_line();
-        for offset in self.screen_position.offset..self.terminal.width().unwrap() {
+        for offset in self.screen_position.offset..width {
             let colors = if on_cursor_line {
                 Colors::Focused
             } else {
@@ -295,15 +389,30 @@ impl<'a, 'p> Renderer<'a, 'p> {
         } else {
             CharStyle::Default
         };
-        // 渲染行号
+        // 行号栏最左侧的一列用于显示插件标记
+        let (sign, sign_style, sign_colors) = self
+            .overlays
+            .gutter_sign(self.buffer_position.line)
+            .unwrap_or((' ', style, Colors::Focused));
         self.render_cell(
             Position {
                 line: self.screen_position.line,
                 offset: 0,
             },
+            sign_style,
+            sign_colors,
+            sign.to_string(),
+        );
+
+        // 渲染行号
+        self.render_cell(
+            Position {
+                line: self.screen_position.line,
+                offset: 1,
+            },
             style,
             Colors::Focused,
-            line_number,
+            line_number[1..].to_string(),
         );
 
         // 行号后的gap
@@ -331,32 +440,23 @@ impl<'a, 'p> Renderer<'a, 'p> {
                 continue;
             }
 
+            self.render_inlay_hints();
             self.set_cursor();
 
             let token_color = to_rgb(self.current_style.foreground);
             let (style, color) = self.current_char_style(token_color);
 
+            if character != "\t" {
+                self.screen_map
+                    .record(self.absolute(self.screen_position), self.buffer_position);
+            }
+
             if self.perferences.line_wrapping()
-                && self.screen_position.offset == self.terminal.width().unwrap() - 1
+                && self.screen_position.offset == self.viewport.width.saturating_sub(1)
             {
                 self.render_cell(self.screen_position, style, color, character.to_string());
                 self.buffer_position.offset += 1;
-
-                // 屏幕上换行但是渲染原来的line
-                let prefix_len = self.content_start_of_line;
-                let prefix = " ".repeat(prefix_len);
-                self.screen_position.offset = 0;
-                self.screen_position.line += 1;
-                self.render_cell(
-                    Position {
-                        line: self.screen_position.line,
-                        offset: self.screen_position.offset,
-                    },
-                    style,
-                    Colors::Default,
-                    prefix,
-                );
-                self.screen_position.offset += prefix_len;
+                self.wrap_screen_line(style);
             } else if character == "\t" {
                 let tab_len = self.perferences.tab_width();
                 let width = tab_len - (self.screen_position.offset + 1) % tab_len;
@@ -373,6 +473,57 @@ impl<'a, 'p> Renderer<'a, 'p> {
         }
     }
 
+    // 屏幕上换行但是渲染原来的line
+    fn wrap_screen_line(&mut self, style: CharStyle) {
+        let prefix_len = self.content_start_of_line;
+        let prefix = " ".repeat(prefix_len);
+        self.screen_position.offset = 0;
+        self.screen_position.line += 1;
+        self.render_cell(
+            Position {
+                line: self.screen_position.line,
+                offset: self.screen_position.offset,
+            },
+            style,
+            Colors::Default,
+            prefix,
+        );
+        self.screen_position.offset += prefix_len;
+    }
+
+    // 渲染当前buffer位置上的行内提示，提示只占用屏幕位置，不推进buffer位置
+    fn render_inlay_hints(&mut self) {
+        if !self.inside_visible() {
+            return;
+        }
+
+        // 目录树面板占满屏幕时编辑区没有宽度
+        let width = self.viewport.width;
+        if width == 0 {
+            return;
+        }
+        for (text, style, colors) in self.overlays.inlay_hints(self.buffer_position) {
+            for character in text.graphemes(true) {
+                if self.screen_position.offset >= width - 1 {
+                    if !self.perferences.line_wrapping() {
+                        break;
+                    }
+                    self.wrap_screen_line(style);
+                }
+                self.render_cell(self.screen_position, style, colors, character.to_string());
+                self.screen_position.offset += 1;
+            }
+        }
+    }
+
+    // 编辑区中的位置在屏幕上的位置
+    fn absolute(&self, position: Position) -> Position {
+        Position {
+            line: position.line,
+            offset: position.offset + self.viewport.left,
+        }
+    }
+
     fn render_cell<C: Into<Cow<'p, str>>>(
         &mut self,
         position: Position,
@@ -380,8 +531,11 @@ impl<'a, 'p> Renderer<'a, 'p> {
         colors: Colors,
         content: C,
     ) {
+        if position.offset >= self.viewport.width {
+            return;
+        }
         self.render_buffer.set_cell(
-            position,
+            self.absolute(position),
             Cell {
                 content: content.into(),
                 colors,
@@ -459,7 +613,7 @@ mod tests {
         },
     };
 
-    use super::Renderer;
+    use super::{Renderer, Viewport};
 
     #[test]
     fn test_display() {
@@ -499,7 +653,10 @@ mod tests {
                 &cached_render_state,
                 &theme,
                 &syntax_set,
-                0,
+                Viewport {
+                    width: terminal.width().unwrap(),
+                    ..Default::default()
+                },
                 todo!(),
             );
             renderer.render(LineIterator::new(&binding), None).unwrap();
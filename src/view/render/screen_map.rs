@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use held_core::utils::position::Position;
+
+/// 记录一次渲染中屏幕坐标与buffer位置的对应关系，用于鼠标定位
+///
+/// 每个屏幕行按列记录渲染的buffer位置，行尾额外记录一个位于最后一个字符之后的位置，
+/// 因此自动换行、行号栏与tab展开都不需要在查询时重新计算。
+#[derive(Debug, Default, Clone)]
+pub struct ScreenMap {
+    lines: BTreeMap<usize, Vec<(usize, Position)>>,
+}
+
+impl ScreenMap {
+    pub fn record(&mut self, screen_position: Position, buffer_position: Position) {
+        self.lines
+            .entry(screen_position.line)
+            .or_default()
+            .push((screen_position.offset, buffer_position));
+    }
+
+    /// 屏幕坐标对应的buffer位置
+    ///
+    /// 点击在行号栏上时为该屏幕行的第一个位置，点击在内容之后时为行尾，
+    /// 点击在最后一行之下时为最后一行上的对应位置。
+    pub fn buffer_position(&self, screen_position: Position) -> Option<Position> {
+        let (_, cells) = self.lines.range(..=screen_position.line).next_back()?;
+        cells
+            .iter()
+            .rev()
+            .find(|(offset, _)| *offset <= screen_position.offset)
+            .or_else(|| cells.first())
+            .map(|(_, position)| *position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use held_core::utils::position::Position;
+
+    use super::ScreenMap;
+
+    #[test]
+    fn clicks_are_clamped_to_rendered_content() {
+        let mut map = ScreenMap::default();
+        // 行号栏宽3，第一行 "ab"，第二行为空
+        map.record(Position::new(0, 3), Position::new(0, 0));
+        map.record(Position::new(0, 4), Position::new(0, 1));
+        map.record(Position::new(0, 5), Position::new(0, 2));
+        map.record(Position::new(1, 3), Position::new(1, 0));
+
+        assert_eq!(
+            map.buffer_position(Position::new(0, 4)),
+            Some(Position::new(0, 1))
+        );
+        assert_eq!(
+            map.buffer_position(Position::new(0, 1)),
+            Some(Position::new(0, 0))
+        );
+        assert_eq!(
+            map.buffer_position(Position::new(0, 40)),
+            Some(Position::new(0, 2))
+        );
+        assert_eq!(
+            map.buffer_position(Position::new(9, 4)),
+            Some(Position::new(1, 0))
+        );
+    }
+}
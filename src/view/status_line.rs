@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use held_core::view::{colors::Colors, status_line::StatusSegment, style::CharStyle};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::buffer::Buffer;
+
+pub const MODE_SEGMENT: &str = "mode";
+pub const INPUT_SEGMENT: &str = "input";
+pub const PATH_SEGMENT: &str = "path";
+pub const MODIFIED_SEGMENT: &str = "modified";
+pub const POSITION_SEGMENT: &str = "position";
+pub const ENCODING_SEGMENT: &str = "encoding";
+pub const KEYS_SEGMENT: &str = "keys";
+
+#[derive(Debug, Clone)]
+pub struct StatusLineData {
+    pub content: String,
+    pub color: Colors,
+    pub style: CharStyle,
+}
+
+/// 状态栏左中右三个区域中依次显示的片段名
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusLineLayout {
+    pub left: Vec<String>,
+    pub center: Vec<String>,
+    pub right: Vec<String>,
+}
+
+impl Default for StatusLineLayout {
+    fn default() -> Self {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        StatusLineLayout {
+            left: names(&[MODE_SEGMENT, INPUT_SEGMENT, PATH_SEGMENT, MODIFIED_SEGMENT]),
+            center: vec![],
+            right: names(&[KEYS_SEGMENT, POSITION_SEGMENT, ENCODING_SEGMENT]),
+        }
+    }
+}
+
+/// 布局后状态栏上的一段内容，`name` 为None时表示片段之间的空白
+#[derive(Debug, Clone)]
+pub struct PlacedSegment {
+    pub name: Option<String>,
+    pub offset: usize,
+    pub width: usize,
+    pub data: StatusLineData,
+}
+
+/// 由各模式填充内置片段，再由Presenter合并插件片段并按照配置布局
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    segments: HashMap<String, StatusLineData>,
+    // 未在布局中引用的插件片段，按插件返回的顺序显示在右侧区域最前面
+    unplaced: Vec<String>,
+}
+
+impl StatusLine {
+    pub fn new(mode_name: &str) -> StatusLine {
+        StatusLine::default().segment(
+            MODE_SEGMENT,
+            StatusLineData {
+                content: format!(" {} ", mode_name),
+                color: Colors::Inverted,
+                style: CharStyle::Bold,
+            },
+        )
+    }
+
+    pub fn segment(mut self, name: &str, data: StatusLineData) -> StatusLine {
+        self.segments.insert(name.to_string(), data);
+        self
+    }
+
+    pub fn input(self, content: String) -> StatusLine {
+        self.segment(
+            INPUT_SEGMENT,
+            StatusLineData {
+                content,
+                color: Colors::Default,
+                style: CharStyle::Default,
+            },
+        )
+    }
+
+    pub fn path(self, content: String, style: CharStyle) -> StatusLine {
+        self.segment(
+            PATH_SEGMENT,
+            StatusLineData {
+                content: format!(" {}", content),
+                color: Colors::Focused,
+                style,
+            },
+        )
+    }
+
+    /// 路径、修改标记、光标位置与编码
+    pub fn buffer(self, buffer: &Option<Buffer>) -> StatusLine {
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => return self,
+        };
+
+        let modified = buffer.modified();
+        let mut status_line = self;
+        if let Some(path) = &buffer.path {
+            let style = if modified {
+                CharStyle::Bold
+            } else {
+                CharStyle::Default
+            };
+            status_line = status_line.path(path.to_string_lossy().to_string(), style);
+        }
+
+        if modified {
+            status_line = status_line.segment(
+                MODIFIED_SEGMENT,
+                StatusLineData {
+                    content: "*".to_string(),
+                    color: Colors::Focused,
+                    style: CharStyle::Bold,
+                },
+            );
+        }
+
+        status_line
+            .segment(
+                POSITION_SEGMENT,
+                StatusLineData {
+                    content: format!(" {}:{} ", buffer.cursor.line + 1, buffer.cursor.offset + 1),
+                    color: Colors::Focused,
+                    style: CharStyle::Default,
+                },
+            )
+            .segment(
+                ENCODING_SEGMENT,
+                StatusLineData {
+                    // buffer内部统一使用utf-8存储
+                    content: " utf-8 ".to_string(),
+                    color: Colors::Inverted,
+                    style: CharStyle::Default,
+                },
+            )
+    }
+
+    /// 等待后续按键的序列前缀
+    pub fn pending_keys(self, keys: &str) -> StatusLine {
+        if keys.is_empty() {
+            return self;
+        }
+        self.segment(
+            KEYS_SEGMENT,
+            StatusLineData {
+                content: format!(" {} ", keys),
+                color: Colors::Focused,
+                style: CharStyle::Default,
+            },
+        )
+    }
+
+    pub fn plugin_segments(&mut self, segments: Vec<StatusSegment>, layout: &StatusLineLayout) {
+        for segment in segments {
+            let placed = layout
+                .left
+                .iter()
+                .chain(&layout.center)
+                .chain(&layout.right)
+                .any(|name| *name == segment.name);
+            if !placed && !self.unplaced.contains(&segment.name) {
+                self.unplaced.push(segment.name.clone());
+            }
+
+            self.segments.insert(
+                segment.name,
+                StatusLineData {
+                    content: segment.content,
+                    color: segment.colors,
+                    style: segment.style,
+                },
+            );
+        }
+    }
+
+    /// 计算每个片段在宽度为width的状态栏上的位置，返回的片段按offset排列并铺满整行
+    ///
+    /// 空间不足时左侧优先，其次右侧，中间区域放不下时整体省略。
+    pub fn layout(&self, layout: &StatusLineLayout, width: usize) -> Vec<PlacedSegment> {
+        let mut right_names = self.unplaced.clone();
+        right_names.extend(layout.right.iter().cloned());
+
+        let left = self.collect(&layout.left);
+        let center = self.collect(&layout.center);
+        let right = self.collect(&right_names);
+
+        let mut placed = Vec::new();
+        let left_end = Self::place(&mut placed, left, 0, width);
+
+        let right_width: usize = right.iter().map(|(_, _, w)| w).sum();
+        let right_start = width.saturating_sub(right_width).max(left_end);
+
+        let center_width: usize = center.iter().map(|(_, _, w)| w).sum();
+        let center_start = width.saturating_sub(center_width) / 2;
+        if center_width > 0
+            && center_start >= left_end
+            && center_start + center_width <= right_start
+        {
+            Self::place(&mut placed, center, center_start, right_start);
+        }
+
+        Self::place(&mut placed, right, right_start, width);
+
+        Self::fill_gaps(placed, width)
+    }
+
+    fn collect<'a>(&'a self, names: &'a [String]) -> Vec<(&'a String, &'a StatusLineData, usize)> {
+        names
+            .iter()
+            .filter_map(|name| {
+                let data = self.segments.get(name)?;
+                let width = data.content.width();
+                (width > 0).then_some((name, data, width))
+            })
+            .collect()
+    }
+
+    // 从start开始依次放置片段，超出limit的部分被截断，返回结束位置
+    fn place(
+        placed: &mut Vec<PlacedSegment>,
+        segments: Vec<(&String, &StatusLineData, usize)>,
+        start: usize,
+        limit: usize,
+    ) -> usize {
+        let mut offset = start;
+        for (name, data, width) in segments {
+            let width = width.min(limit.saturating_sub(offset));
+            if width == 0 {
+                break;
+            }
+
+            let mut data = data.clone();
+            data.content = fit_width(&data.content, width);
+            placed.push(PlacedSegment {
+                name: Some(name.clone()),
+                offset,
+                width,
+                data,
+            });
+            offset += width;
+        }
+        offset
+    }
+
+    fn fill_gaps(mut placed: Vec<PlacedSegment>, width: usize) -> Vec<PlacedSegment> {
+        placed.sort_by_key(|segment| segment.offset);
+
+        let mut ret = Vec::new();
+        let mut offset = 0;
+        for segment in placed.into_iter().chain(std::iter::once(PlacedSegment {
+            name: None,
+            offset: width,
+            width: 0,
+            data: Self::blank(0),
+        })) {
+            if segment.offset > offset {
+                ret.push(PlacedSegment {
+                    name: None,
+                    offset,
+                    width: segment.offset - offset,
+                    data: Self::blank(segment.offset - offset),
+                });
+            }
+            offset = segment.offset + segment.width;
+            if segment.width > 0 {
+                ret.push(segment);
+            }
+        }
+        ret
+    }
+
+    fn blank(width: usize) -> StatusLineData {
+        StatusLineData {
+            content: " ".repeat(width),
+            color: Colors::Focused,
+            style: CharStyle::Default,
+        }
+    }
+}
+
+/// 截取显示宽度不超过width的前缀，放不下的宽字符以空格补足，结果的显示宽度为width
+pub fn fit_width(content: &str, width: usize) -> String {
+    let mut ret = String::new();
+    let mut used = 0;
+    for grapheme in content.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width > width {
+            break;
+        }
+        ret.push_str(grapheme);
+        used += grapheme_width;
+    }
+    ret.push_str(&" ".repeat(width - used));
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use held_core::view::{colors::Colors, status_line::StatusSegment, style::CharStyle};
+
+    use super::{fit_width, StatusLine, StatusLineLayout};
+
+    fn summary(status_line: &StatusLine, layout: &StatusLineLayout, width: usize) -> String {
+        status_line
+            .layout(layout, width)
+            .iter()
+            .map(|segment| segment.data.content.clone())
+            .collect()
+    }
+
+    #[test]
+    fn segments_are_arranged_by_layout() {
+        let status_line = StatusLine::new("NORMAL").input(":w".to_string());
+        let layout = StatusLineLayout {
+            left: vec!["mode".to_string()],
+            center: vec!["input".to_string()],
+            right: vec!["missing".to_string()],
+        };
+
+        assert_eq!(summary(&status_line, &layout, 20), " NORMAL  :w         ");
+    }
+
+    #[test]
+    fn unplaced_plugin_segments_go_right() {
+        let mut status_line = StatusLine::new("NORMAL");
+        let layout = StatusLineLayout::default();
+        status_line.plugin_segments(
+            vec![StatusSegment {
+                name: "git".to_string(),
+                content: "main".to_string(),
+                style: CharStyle::Default,
+                colors: Colors::Default,
+            }],
+            &layout,
+        );
+
+        assert_eq!(summary(&status_line, &layout, 16), " NORMAL     main");
+    }
+
+    #[test]
+    fn left_segments_take_priority() {
+        let status_line =
+            StatusLine::new("NORMAL").path("src/main.rs".to_string(), CharStyle::Bold);
+        let layout = StatusLineLayout::default();
+
+        let placed = status_line.layout(&layout, 12);
+        assert_eq!(placed.len(), 2);
+        assert_eq!(placed[1].data.content, " src");
+    }
+
+    #[test]
+    fn wide_characters_are_measured_by_display_width() {
+        let status_line = StatusLine::new("NORMAL").path("文件.rs".to_string(), CharStyle::Bold);
+        let layout = StatusLineLayout::default();
+
+        let placed = status_line.layout(&layout, 16);
+        assert_eq!(placed[1].offset, 8);
+        assert_eq!(placed[1].width, 8);
+        assert_eq!(summary(&status_line, &layout, 16), " NORMAL  文件.rs");
+        assert_eq!(summary(&status_line, &layout, 11), " NORMAL  文");
+        assert_eq!(fit_width("文件", 3), "文 ");
+    }
+}
@@ -1,12 +1,18 @@
 use std::{
     cell::{RefCell, RefMut},
     io::{stdout, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
 use crossterm::{
-    event::Event,
-    terminal::{self, disable_raw_mode},
-    QueueableCommand,
+    cursor::{SetCursorStyle, Show},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
+    },
+    style::{Attribute, SetAttribute},
+    terminal::{self, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand, QueueableCommand,
 };
 use held_core::{
     utils::position::Position,
@@ -16,6 +22,27 @@ use held_core::{
 use super::{Terminal, MIN_HEIGHT, MIN_WIDTH, TERMINAL_EXECUTE_ERROR};
 use crate::errors::*;
 
+// 终端当前是否由held接管，保证恢复操作只执行一次
+static ENTERED: AtomicBool = AtomicBool::new(false);
+
+/// 恢复终端的原始状态，终端未被接管时什么也不做
+///
+/// 除了正常退出与挂起，panic与错误退出时同样需要恢复，因此不依赖终端实例。
+pub fn restore_terminal() {
+    if !ENTERED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut stdout = stdout();
+    let _ = stdout.queue(SetAttribute(Attribute::Reset));
+    let _ = stdout.queue(SetCursorStyle::DefaultUserShape);
+    let _ = stdout.queue(Show);
+    let _ = stdout.queue(DisableMouseCapture);
+    let _ = stdout.queue(DisableBracketedPaste);
+    let _ = stdout.queue(LeaveAlternateScreen);
+    let _ = stdout.flush();
+    let _ = disable_raw_mode();
+}
+
 #[derive(Debug)]
 pub struct CrossTerminal {
     ansi_buffer: RefCell<Vec<u8>>,
@@ -26,7 +53,7 @@ unsafe impl Sync for CrossTerminal {}
 
 impl CrossTerminal {
     pub fn new() -> Result<CrossTerminal> {
-        crossterm::terminal::enable_raw_mode()?;
+        Self::enter()?;
         let terminal = CrossTerminal {
             ansi_buffer: RefCell::default(),
         };
@@ -35,6 +62,16 @@ impl CrossTerminal {
         Ok(terminal)
     }
 
+    // 进入raw mode与备用屏幕，并开启鼠标与粘贴事件
+    fn enter() -> Result<()> {
+        crossterm::terminal::enable_raw_mode()?;
+        ENTERED.store(true, Ordering::SeqCst);
+        stdout().execute(EnterAlternateScreen)?;
+        stdout().execute(EnableMouseCapture)?;
+        stdout().execute(EnableBracketedPaste)?;
+        Ok(())
+    }
+
     fn buffer(&self) -> RefMut<Vec<u8>> {
         return self.ansi_buffer.borrow_mut();
     }
@@ -95,6 +132,10 @@ impl Terminal for CrossTerminal {
         crossterm::event::read().chain_err(|| "Handle event io error")
     }
 
+    fn poll(&self, timeout: Duration) -> Result<bool> {
+        crossterm::event::poll(timeout).chain_err(|| "Handle event io error")
+    }
+
     fn clear(&self) -> Result<()> {
         self.buffer()
             .queue(crossterm::style::SetAttribute(
@@ -169,18 +210,17 @@ impl Terminal for CrossTerminal {
     }
 
     fn suspend(&self) {
-        let _ = self.clear();
-        let _ = self.set_cursor(Some(Position::from((0, 0))));
-        let _ = stdout().write_all(&self.buffer());
-        let _ = stdout().flush();
-
         self.buffer().clear();
+        restore_terminal();
+    }
+
+    fn resume(&self) -> Result<()> {
+        Self::enter()
     }
 }
 
 impl Drop for CrossTerminal {
     fn drop(&mut self) {
         self.suspend();
-        let _ = disable_raw_mode();
     }
 }
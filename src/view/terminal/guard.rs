@@ -0,0 +1,26 @@
+use std::{backtrace::Backtrace, panic};
+
+use super::cross_terminal::restore_terminal;
+
+/// 在离开作用域时恢复终端，覆盖错误返回等所有退出路径
+///
+/// 创建时同时安装panic hook：panic时先恢复终端再输出panic信息，并将崩溃报告写入日志。
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn install() -> TerminalGuard {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            error!("held crashed: {info}\n{}", Backtrace::force_capture());
+            default_hook(info);
+        }));
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
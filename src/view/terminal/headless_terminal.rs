@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use crossterm::event::Event;
+use held_core::{
+    utils::position::Position,
+    view::{colors::Colors, style::CharStyle},
+};
+
+use super::Terminal;
+use crate::errors::*;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 24;
+
+/// 无界面模式使用的终端，不开启raw mode，也不输出任何内容
+#[derive(Debug, Default)]
+pub struct HeadlessTerminal;
+
+impl Terminal for HeadlessTerminal {
+    fn listen(&self) -> Result<Event> {
+        Err("Headless terminal has no input".into())
+    }
+
+    fn poll(&self, _: Duration) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn clear(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn present(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn width(&self) -> Result<usize> {
+        Ok(WIDTH)
+    }
+
+    fn height(&self) -> Result<usize> {
+        Ok(HEIGHT)
+    }
+
+    fn set_cursor(&self, _: Option<Position>) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_cursor_type(&self, _: crossterm::cursor::SetCursorStyle) -> Result<()> {
+        Ok(())
+    }
+
+    fn print(&self, _: &Position, _: CharStyle, _: Colors, _: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn suspend(&self) {}
+
+    fn resume(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
 use crate::errors::*;
 use crossterm::event::Event;
@@ -7,6 +7,8 @@ use held_core::view::colors::Colors;
 use held_core::view::style::CharStyle;
 
 pub mod cross_terminal;
+pub mod guard;
+pub mod headless_terminal;
 
 pub(super) const MIN_WIDTH: u16 = 10;
 pub(super) const MIN_HEIGHT: u16 = 10;
@@ -16,6 +18,7 @@ pub const TERMINAL_EXECUTE_ERROR: &'static str = "Terminal IO Error";
 #[allow(dead_code)]
 pub trait Terminal: Send + Sync + Debug {
     fn listen(&self) -> Result<Event>;
+    fn poll(&self, timeout: Duration) -> Result<bool>;
     fn clear(&self) -> Result<()>;
     fn present(&self) -> Result<()>;
     fn width(&self) -> Result<usize>;
@@ -23,5 +26,8 @@ pub trait Terminal: Send + Sync + Debug {
     fn set_cursor(&self, _: Option<Position>) -> Result<()>;
     fn set_cursor_type(&self, _: crossterm::cursor::SetCursorStyle) -> Result<()>;
     fn print(&self, _: &Position, _: CharStyle, _: Colors, _: &str) -> Result<()>;
+    /// 恢复终端的原始状态，之后可以运行其他程序或退出
     fn suspend(&self);
+    /// 从suspend中恢复，调用方需要重绘整个屏幕
+    fn resume(&self) -> Result<()>;
 }
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crossterm::style::Color;
+use held_core::view::{colors::Colors, style::CharStyle};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    modules::{
+        file_tree::{FileTree, TreeEntry},
+        git_status::{GitStatus, GitStatuses},
+        perferences::Perferences,
+    },
+    workspace::Workspace,
+};
+
+/// 停靠在编辑区左侧的目录树面板
+///
+/// 面板直接绘制在屏幕上，与编辑区分别获得焦点：工作区模式下按键作用于面板，
+/// 其他模式下面板只随文件的打开与修改更新显示。
+#[derive(Default)]
+pub struct TreePanel {
+    pub tree: FileTree,
+    git: GitStatuses,
+    visible: bool,
+    focused: bool,
+    icons: bool,
+    // 面板第一行显示的目录树行
+    scroll: usize,
+    // 已打开的文件的ino与其是否有未保存的修改
+    opened: HashMap<u64, bool>,
+}
+
+/// 面板中的一行
+pub struct PanelRow {
+    pub text: String,
+    pub style: CharStyle,
+    pub colors: Colors,
+    // 显示在行尾的git状态
+    pub marker: Option<(&'static str, Colors)>,
+}
+
+impl TreePanel {
+    pub fn new(tree: FileTree) -> TreePanel {
+        TreePanel {
+            git: GitStatuses::new(tree.root()),
+            tree,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// 渲染之前同步设置、焦点、目录树与文件的修改状态，并保持所选行在height行之内可见
+    ///
+    /// git状态在目录树重新遍历、打开的文件的修改状态改变（如保存）或暂存区改变时在后台重新读取，
+    /// 读取完成后的第一次同步时显示。
+    pub fn sync(
+        &mut self,
+        workspace: &Workspace,
+        perferences: &dyn Perferences,
+        focused: bool,
+        height: usize,
+    ) {
+        self.focused = focused;
+        if !self.visible {
+            return;
+        }
+        self.icons = perferences.tree_icons();
+        self.tree.set_show_hidden(perferences.tree_show_hidden());
+        self.git.poll();
+        let walked = self.tree.update();
+        let opened: HashMap<u64, bool> = self
+            .tree
+            .entries()
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .filter_map(|entry| {
+                let buffer = workspace.get_buffer_with_ino(entry.ino)?;
+                Some((entry.ino, buffer.modified()))
+            })
+            .collect();
+        if walked || opened != self.opened || self.git.index_changed() {
+            self.git.refresh();
+        }
+        self.opened = opened;
+
+        let selected = self.tree.selected();
+        if selected < self.scroll {
+            self.scroll = selected;
+        } else if height > 0 && selected >= self.scroll + height {
+            self.scroll = selected + 1 - height;
+        }
+        self.scroll = self
+            .scroll
+            .min(self.tree.entries().len().saturating_sub(height.max(1)));
+    }
+
+    /// 是否在等待后台的git状态或文件遍历，等待期间需要定时重绘
+    pub fn is_busy(&self) -> bool {
+        self.visible && (self.git.is_running() || self.tree.is_walking())
+    }
+
+    /// 面板中第row行显示的目录树行
+    pub fn entry_at(&self, row: usize) -> usize {
+        self.scroll + row
+    }
+
+    /// 自上而下各行的内容与样式，每行不超过width个字素，最后一列留给git状态
+    pub fn rows(&self, height: usize, width: usize) -> Vec<PanelRow> {
+        let text_width = width.saturating_sub(2);
+        self.tree
+            .entries()
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .map(|(index, entry)| {
+                let selected = index == self.tree.selected();
+                let marker = match (self.icons, entry.is_dir, entry.is_open) {
+                    (true, _, _) => format!("{} ", icon(entry)),
+                    (false, true, true) => "- ".to_string(),
+                    (false, true, false) => "+ ".to_string(),
+                    (false, false, _) => "| ".to_string(),
+                };
+                let name = entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| entry.path.to_string_lossy().to_string());
+                let mut text: String = format!("{}{}{}", " ".repeat(entry.depth), marker, name)
+                    .graphemes(true)
+                    .take(text_width)
+                    .collect();
+                if selected {
+                    // 所选行的背景填满整行
+                    let len = text.graphemes(true).count();
+                    text.push_str(&" ".repeat(width - len));
+                }
+
+                let style = if index == 0 || selected || self.tree.is_match(&entry.path) {
+                    CharStyle::Bold
+                } else {
+                    CharStyle::Default
+                };
+                let colors = if selected {
+                    if self.focused {
+                        Colors::Inverted
+                    } else {
+                        Colors::Focused
+                    }
+                } else {
+                    match self.opened.get(&entry.ino) {
+                        Some(true) => Colors::CustomForeground(Color::Yellow),
+                        Some(false) => Colors::CustomForeground(Color::Green),
+                        None => Colors::Default,
+                    }
+                };
+                let marker = self.git.get(&entry.path).map(|status| match status {
+                    GitStatus::Untracked => ("U", Colors::CustomForeground(Color::Green)),
+                    GitStatus::Added => ("A", Colors::CustomForeground(Color::Green)),
+                    GitStatus::Modified => ("M", Colors::CustomForeground(Color::Yellow)),
+                    GitStatus::Conflicted => ("!", Colors::CustomForeground(Color::Red)),
+                });
+                PanelRow {
+                    text,
+                    style,
+                    colors,
+                    marker,
+                }
+            })
+            .collect()
+    }
+}
+
+// 按文件名与扩展名选择的Nerd Font图标
+fn icon(entry: &TreeEntry) -> &'static str {
+    if entry.is_dir {
+        return if entry.is_open {
+            "\u{f07c}"
+        } else {
+            "\u{f07b}"
+        };
+    }
+    let name = entry
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match name.as_str() {
+        "cargo.toml" | "cargo.lock" => return "\u{e7a8}",
+        "makefile" | "cmakelists.txt" => return "\u{e779}",
+        "dockerfile" => return "\u{f308}",
+        "license" => return "\u{f0219}",
+        ".gitignore" | ".gitmodules" | ".gitattributes" => return "\u{e702}",
+        _ => {}
+    }
+    let extension = name.rsplit_once('.').map_or("", |(_, extension)| extension);
+    match extension {
+        "rs" => "\u{e7a8}",
+        "c" => "\u{e61e}",
+        "h" | "hpp" => "\u{f0fd}",
+        "cc" | "cpp" | "cxx" => "\u{e61d}",
+        "py" => "\u{e606}",
+        "go" => "\u{e627}",
+        "js" | "mjs" => "\u{e74e}",
+        "ts" => "\u{e628}",
+        "java" => "\u{e738}",
+        "lua" => "\u{e620}",
+        "sh" | "bash" | "zsh" => "\u{f489}",
+        "html" | "htm" => "\u{e736}",
+        "css" => "\u{e749}",
+        "json" => "\u{e60b}",
+        "toml" | "yaml" | "yml" | "ini" | "conf" => "\u{e615}",
+        "md" => "\u{e609}",
+        "txt" | "log" => "\u{f15c}",
+        "lock" => "\u{f023}",
+        "png" | "jpg" | "jpeg" | "gif" | "svg" => "\u{f1c5}",
+        "zip" | "tar" | "gz" | "xz" => "\u{f410}",
+        "s" | "asm" => "\u{e637}",
+        "ld" | "lds" => "\u{f471}",
+        _ => "\u{f15b}",
+    }
+}
@@ -12,6 +12,7 @@ use crate::{
     view::monitor::Monitor,
 };
 use syntect::parsing::SyntaxSet;
+use walkdir::{DirEntryExt, WalkDir};
 
 use crate::buffer::Buffer;
 
@@ -138,6 +139,124 @@ impl Workspace {
         self.buffer_ida
     }
 
+    /// 所有buffer的id，按打开的顺序排列
+    pub fn buffer_ids(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .buffers
+            .keys()
+            .copied()
+            .chain(self.current_buffer.as_ref().and_then(|buffer| buffer.id))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// 打开path对应的文件并设为当前buffer，相对路径相对于工作区
+    ///
+    /// 已经打开的文件直接切换到对应的buffer；文件不存在时创建以path为路径的空buffer，
+    /// 保存时才写入磁盘。
+    pub fn open_buffer(&mut self, path: &Path, monitor: &mut Monitor) -> Result<usize> {
+        let path = self.path.join(path);
+        let opened = path
+            .metadata()
+            .ok()
+            .and_then(|metadata| self.buffers_ino_map.get(&metadata.ino()).copied())
+            .filter(|id| self.get_buffer(*id).is_some());
+        if let Some(id) = opened {
+            self.select_buffer(id);
+            return Ok(id);
+        }
+
+        let buffer = if path.exists() {
+            Buffer::from_file(&path)?
+        } else {
+            let mut buffer = Buffer::new();
+            buffer.path = Some(path);
+            buffer
+        };
+        let id = self.add_buffer_with_select(buffer);
+        monitor.init_buffer(self.current_buffer.as_mut().unwrap())?;
+        Ok(id)
+    }
+
+    /// buffer显示的名字，工作区内的文件显示相对路径
+    pub fn buffer_name(&self, buffer: &Buffer) -> Option<String> {
+        let path = buffer.path.as_ref()?;
+        let path = path.strip_prefix(&self.path).unwrap_or(path);
+        Some(path.to_string_lossy().to_string())
+    }
+
+    /// 有未保存修改的buffer的id
+    pub fn modified_buffer_ids(&self) -> Vec<usize> {
+        self.buffer_ids()
+            .into_iter()
+            .filter(|id| self.get_buffer(*id).is_some_and(Buffer::modified))
+            .collect()
+    }
+
+    /// 有未保存修改的buffer的文件名，用引号括起并以逗号分隔，没有时返回None
+    pub fn modified_buffer_names(&self) -> Option<String> {
+        let names: Vec<String> = self
+            .modified_buffer_ids()
+            .into_iter()
+            .filter_map(|id| self.get_buffer(id))
+            .map(|buffer| {
+                format!(
+                    "\"{}\"",
+                    buffer
+                        .file_name()
+                        .unwrap_or_else(|| "[No Name]".to_string())
+                )
+            })
+            .collect();
+        if names.is_empty() {
+            None
+        } else {
+            Some(names.join(", "))
+        }
+    }
+
+    /// 文件或目录从from移动到to之后，让其中已打开文件的buffer指向新的路径
+    ///
+    /// 移动不改变文件的ino，通过 `buffers_ino_map` 找到对应的buffer。
+    pub fn repoint_buffers(&mut self, from: &Path, to: &Path) {
+        for entry in WalkDir::new(to).into_iter().filter_map(|entry| entry.ok()) {
+            let id = match self.buffers_ino_map.get(&entry.ino()) {
+                Some(id) => *id,
+                None => continue,
+            };
+            let moved = match self.get_buffer_mut(id) {
+                Some(buffer)
+                    if buffer
+                        .path
+                        .as_ref()
+                        .is_some_and(|path| path.starts_with(from)) =>
+                {
+                    let extension = buffer.file_extension();
+                    buffer.path = Some(entry.path().to_path_buf());
+                    extension != buffer.file_extension()
+                }
+                _ => false,
+            };
+            // 扩展名改变后重新选择语法
+            if moved {
+                let _ = self.update_buffer_syntax(id);
+            }
+        }
+    }
+
+    /// 删除path之前调用，移除其中的文件在 `buffers_ino_map` 中的记录
+    ///
+    /// 避免被删除文件的ino被新文件重用时误认为已经打开。
+    pub fn forget_files(&mut self, path: &Path) {
+        for entry in WalkDir::new(path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+        {
+            self.buffers_ino_map.remove(&entry.ino());
+        }
+    }
+
     pub fn get_buffer(&self, id: usize) -> Option<&Buffer> {
         if let Some(ref buffer) = self.current_buffer {
             if buffer.id.unwrap() == id {
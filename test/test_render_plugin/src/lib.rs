@@ -2,7 +2,12 @@ use held_core::{
     declare_plugin, interface,
     plugin::Plugin,
     utils::{position::Position, rectangle::Rectangle},
-    view::{colors::Colors, render::ContentRenderBuffer, style::CharStyle},
+    view::{
+        colors::{Color, Colors},
+        render::{overlay::Overlay, ContentRenderBuffer},
+        status_line::StatusSegment,
+        style::CharStyle,
+    },
 };
 
 declare_plugin!(RenderTestPlugin, RenderTestPlugin::new);
@@ -20,34 +25,69 @@ impl Plugin for RenderTestPlugin {
         "render test plugin"
     }
 
-    fn init(&self) {}
+    // 调用会修改编辑器状态的宿主函数
+    fn init(&self) {
+        interface::cursor::move_down();
+    }
 
     fn deinit(&self) {}
 
     fn on_render_content(&self) -> Vec<ContentRenderBuffer> {
-        let cursor_position = interface::cursor::screen_cursor_position();
-
-        let width = 10;
         let mut buffer = ContentRenderBuffer::new(Rectangle {
-            position: Position {
-                line: cursor_position.line.saturating_sub(1),
-                offset: cursor_position.offset - width / 2,
-            },
-            width,
+            position: Position::new(0, 0),
+            width: 4,
             height: 1,
         });
-
-        let buffer_str = format!("{}:{}", cursor_position.offset, cursor_position.line);
         buffer.put_buffer(
-            Position {
-                line: 0,
-                offset: (width - buffer_str.len()) / 2,
-            },
-            buffer_str,
+            Position::new(0, 0),
+            "wasm".to_string(),
             CharStyle::Bold,
-            Colors::Warning,
+            Colors::Custom(Color::Rgb { r: 1, g: 2, b: 3 }, Color::AnsiValue(9)),
         );
-
         vec![buffer]
     }
+
+    fn on_render_overlays(&self) -> Vec<Overlay> {
+        let cursor_position = interface::cursor::cursor_position();
+
+        vec![
+            Overlay::Popup {
+                anchor: cursor_position,
+                lines: vec![format!(
+                    " {}:{} ",
+                    cursor_position.line + 1,
+                    cursor_position.offset + 1
+                )],
+                style: CharStyle::Bold,
+                colors: Colors::Warning,
+            },
+            Overlay::VirtualText {
+                line: cursor_position.line,
+                text: "render test".to_string(),
+                style: CharStyle::Italic,
+                colors: Colors::CustomForeground(Color::DarkGrey),
+            },
+            Overlay::InlayHint {
+                position: cursor_position,
+                text: "|".to_string(),
+                style: CharStyle::Default,
+                colors: Colors::CustomFocusedForeground(Color::Cyan),
+            },
+            Overlay::GutterSign {
+                line: cursor_position.line,
+                sign: '>',
+                style: CharStyle::Bold,
+                colors: Colors::Warning,
+            },
+        ]
+    }
+
+    fn on_render_status_line(&self) -> Vec<StatusSegment> {
+        vec![StatusSegment {
+            name: "render_test".to_string(),
+            content: " wasm ".to_string(),
+            style: CharStyle::Bold,
+            colors: Colors::Warning,
+        }]
+    }
 }